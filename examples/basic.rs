@@ -1,10 +1,10 @@
 use impit::impit::Impit;
-use impit::emulation::Browser;
+use impit::emulation::{Browser, FirefoxVersion};
  
  #[tokio::main]
  async fn main() {
-    let mut impit = Impit::builder()
-        .with_browser(Browser::Firefox)
+    let impit = Impit::builder()
+        .with_browser(Browser::Firefox(FirefoxVersion::V133))
         .with_http3()
         .build();
 
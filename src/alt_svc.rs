@@ -0,0 +1,175 @@
+use std::time::Duration;
+
+use reqwest::header::HeaderMap;
+
+/// The freshness of an alternative without the `ma` parameter (RFC 7838, section 3.1).
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// An alternative service advertised by the `Alt-Svc` header (RFC 7838), e.g. `h3=":443"; ma=86400`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alternative {
+  /// The ALPN protocol ID (percent-decoded), e.g. `h3`.
+  pub protocol: String,
+  /// The host of the alternative service (without the IPv6 brackets). `None` if the service runs on the origin's host.
+  pub host: Option<String>,
+  pub port: u16,
+  /// How long the alternative can be used, from the `ma` parameter. Defaults to 24 hours.
+  pub max_age: Duration,
+  /// Whether the alternative survives network changes, from the `persist` parameter.
+  pub persist: bool,
+}
+
+/// The value of the `Alt-Svc` header.
+///
+/// ### Example
+/// ```rust
+/// let alt_svc = AltSvc::parse("h3=\":443\"; ma=3600, h2=\"alt.example.com:443\"");
+///
+/// assert_eq!(alt_svc.alternatives("h3").next().unwrap().max_age, Duration::from_secs(3600));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AltSvc {
+  /// `Alt-Svc: clear`, invalidating all the alternatives of the origin.
+  Clear,
+  /// The alternatives, in the order of the server's preference. They replace the alternatives advertised before.
+  Alternatives(Vec<Alternative>),
+}
+
+impl AltSvc {
+  /// Parses the `Alt-Svc` header value. The invalid alternatives are skipped.
+  pub fn parse(value: &str) -> AltSvc {
+    if value.trim().eq_ignore_ascii_case("clear") {
+      return AltSvc::Clear;
+    }
+
+    AltSvc::Alternatives(split_unquoted(value, ',').into_iter().filter_map(parse_alternative).collect())
+  }
+
+  /// Parses the `Alt-Svc` headers of a response, or returns `None` if there are none.
+  pub fn from_headers(headers: &HeaderMap) -> Option<AltSvc> {
+    let values: Vec<&str> = headers.get_all("alt-svc").iter()
+      .filter_map(|value| value.to_str().ok())
+      .collect();
+
+    match values.is_empty() {
+      true => None,
+      false => Some(AltSvc::parse(&values.join(","))),
+    }
+  }
+
+  /// Returns the alternatives using the given protocol, in the order of preference.
+  pub fn alternatives<'a>(&'a self, protocol: &'a str) -> impl Iterator<Item = &'a Alternative> + 'a {
+    let alternatives = match self {
+      AltSvc::Clear => &[][..],
+      AltSvc::Alternatives(alternatives) => alternatives.as_slice(),
+    };
+
+    alternatives.iter().filter(move |alternative| alternative.protocol == protocol)
+  }
+}
+
+/// Parses `protocol-id "=" alt-authority *( OWS ";" OWS parameter )`.
+fn parse_alternative(value: &str) -> Option<Alternative> {
+  let mut parts = split_unquoted(value, ';').into_iter();
+  let (protocol, authority) = parts.next()?.split_once('=')?;
+
+  let protocol = percent_decode(protocol.trim())?;
+  let authority = unquote(authority.trim())?;
+  let (host, port) = authority.rsplit_once(':')?;
+
+  let host = match host.trim_start_matches('[').trim_end_matches(']') {
+    "" => None,
+    host => Some(host.to_ascii_lowercase()),
+  };
+
+  let mut alternative = Alternative {
+    protocol,
+    host,
+    port: port.parse().ok()?,
+    max_age: DEFAULT_MAX_AGE,
+    persist: false,
+  };
+
+  for parameter in parts {
+    let Some((name, value)) = parameter.split_once('=') else {
+      continue;
+    };
+
+    let value = value.trim();
+    let value = unquote(value).unwrap_or_else(|| value.to_string());
+
+    match name.trim().to_ascii_lowercase().as_str() {
+      // Larger values are capped, like the delta-seconds in the `Cache-Control` header.
+      "ma" => if let Ok(seconds) = value.parse::<u64>() {
+        alternative.max_age = Duration::from_secs(seconds.min(u32::MAX.into()));
+      },
+      "persist" => alternative.persist = value == "1",
+      _ => {},
+    }
+  }
+
+  Some(alternative)
+}
+
+/// Splits the value on the separator, except in the quoted strings.
+fn split_unquoted(value: &str, separator: char) -> Vec<&str> {
+  let mut parts = vec![];
+  let mut start = 0;
+  let mut quoted = false;
+  let mut escaped = false;
+
+  for (index, c) in value.char_indices() {
+    match c {
+      _ if escaped => escaped = false,
+      '\\' if quoted => escaped = true,
+      '"' => quoted = !quoted,
+      c if c == separator && !quoted => {
+        parts.push(&value[start..index]);
+        start = index + 1;
+      },
+      _ => {},
+    }
+  }
+
+  parts.push(&value[start..]);
+  parts.into_iter().filter(|part| !part.trim().is_empty()).collect()
+}
+
+/// Returns the content of a quoted string, or `None` if the value isn't quoted.
+fn unquote(value: &str) -> Option<String> {
+  let value = value.strip_prefix('"')?.strip_suffix('"')?;
+  let mut unquoted = String::with_capacity(value.len());
+  let mut chars = value.chars();
+
+  while let Some(c) = chars.next() {
+    match c {
+      '\\' => unquoted.extend(chars.next()),
+      c => unquoted.push(c),
+    }
+  }
+
+  Some(unquoted)
+}
+
+/// Decodes the percent-encoded protocol ID, e.g. `w%3Dx%3Ay` to `w=x:y`.
+fn percent_decode(value: &str) -> Option<String> {
+  let bytes = value.as_bytes();
+  let mut decoded = Vec::with_capacity(bytes.len());
+  let mut index = 0;
+
+  while index < bytes.len() {
+    match bytes[index] {
+      b'%' => {
+        let hex = std::str::from_utf8(bytes.get(index + 1..index + 3)?).ok()?;
+        decoded.push(u8::from_str_radix(hex, 16).ok()?);
+        index += 3;
+      },
+      byte => {
+        decoded.push(byte);
+        index += 1;
+      },
+    }
+  }
+
+  String::from_utf8(decoded).ok()
+}
@@ -0,0 +1,459 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use hickory_proto::rr::rdata::svcb::{SvcParamValue, SVCB};
+use hickory_proto::rr::{RData, RecordType};
+use hickory_resolver::config::{NameServerConfig, Protocol, ResolverConfig, ResolverOpts};
+use hickory_resolver::error::ResolveErrorKind;
+use hickory_resolver::TokioAsyncResolver;
+use log::debug;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+
+/// How long a missing record is cached if the nameserver doesn't say (i.e. sends no `SOA` record).
+const DEFAULT_NEGATIVE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// The longest time an answer is cached, the cap recommended by RFC 8767.
+const MAX_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// The error type of the DNS lookups.
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// The future returned by the [`Resolver`] lookups.
+pub type DnsFuture<'a, T> = Pin<Box<dyn Future<Output = Result<DnsAnswer<T>, BoxError>> + Send + 'a>>;
+
+/// The records found by a DNS lookup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DnsAnswer<T> {
+  pub records: Vec<T>,
+  /// How long the records can be cached.
+  pub ttl: Duration,
+}
+
+/// An `HTTPS` DNS record (RFC 9460), describing how the host can be reached.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HttpsRecord {
+  /// `0` for the AliasMode records, which point to the `HTTPS` records of the `target` name.
+  /// Otherwise a ServiceMode record, the records with lower priority are preferred.
+  pub priority: u16,
+  /// The target name, without the trailing dot. Empty if the record points to its owner name (`.`).
+  pub target: String,
+  /// The ALPN protocols supported by the endpoint, e.g. `h3` or `h2`.
+  pub alpn: Vec<String>,
+  /// The port of the endpoint, if it differs from the origin's port.
+  pub port: Option<u16>,
+  /// The IPv4 addresses of the target (`ipv4hint`).
+  pub ipv4_hints: Vec<Ipv4Addr>,
+  /// The IPv6 addresses of the target (`ipv6hint`).
+  pub ipv6_hints: Vec<Ipv6Addr>,
+  /// The Encrypted Client Hello configuration (`ECHConfigList`) of the endpoint.
+  pub ech_config: Option<Vec<u8>>,
+}
+
+impl HttpsRecord {
+  /// Whether this is an AliasMode record.
+  pub fn is_alias(&self) -> bool {
+    self.priority == 0
+  }
+}
+
+/// Resolves the hosts for [`Impit`](crate::impit::Impit) - both the addresses to connect to and the `HTTPS` records used for discovering the HTTP/3 support.
+///
+/// The default implementation is [`HickoryResolver`]. A custom resolver can be set with [`ImpitBuilder::with_resolver`](crate::impit::ImpitBuilder::with_resolver),
+/// e.g. to resolve the hosts with a service discovery system or to mock the DNS in tests.
+/// The answers are cached by `Impit` for their TTL.
+pub trait Resolver: Send + Sync {
+  /// Returns the IP addresses of the host.
+  fn lookup_ip<'a>(&'a self, host: &'a str) -> DnsFuture<'a, IpAddr>;
+
+  /// Returns the `HTTPS` records of the host.
+  fn lookup_https<'a>(&'a self, host: &'a str) -> DnsFuture<'a, HttpsRecord>;
+}
+
+impl std::fmt::Debug for dyn Resolver {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "Resolver")
+  }
+}
+
+/// The protocol used for talking to a nameserver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DnsProtocol {
+  /// Plain DNS over UDP (with TCP fallback for truncated responses).
+  Udp,
+  /// Plain DNS over TCP.
+  Tcp,
+  /// DNS-over-HTTPS (RFC 8484).
+  Https,
+  /// DNS-over-TLS (RFC 7858).
+  Tls,
+}
+
+/// A nameserver used for the DNS lookups.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameServer {
+  /// The address of the nameserver, e.g. `1.1.1.1:53` or `1.1.1.1:443` for DNS-over-HTTPS.
+  pub address: SocketAddr,
+  pub protocol: DnsProtocol,
+  /// The name in the nameserver's TLS certificate (e.g. `cloudflare-dns.com`). Required for DNS-over-HTTPS and DNS-over-TLS.
+  pub tls_name: Option<String>,
+}
+
+impl NameServer {
+  /// A nameserver talking plain DNS over UDP.
+  pub fn udp(address: SocketAddr) -> Self {
+    NameServer { address, protocol: DnsProtocol::Udp, tls_name: None }
+  }
+
+  /// A nameserver talking plain DNS over TCP.
+  pub fn tcp(address: SocketAddr) -> Self {
+    NameServer { address, protocol: DnsProtocol::Tcp, tls_name: None }
+  }
+
+  /// A DNS-over-HTTPS nameserver.
+  pub fn https(address: SocketAddr, tls_name: impl Into<String>) -> Self {
+    NameServer { address, protocol: DnsProtocol::Https, tls_name: Some(tls_name.into()) }
+  }
+
+  /// A DNS-over-TLS nameserver.
+  pub fn tls(address: SocketAddr, tls_name: impl Into<String>) -> Self {
+    NameServer { address, protocol: DnsProtocol::Tls, tls_name: Some(tls_name.into()) }
+  }
+}
+
+/// Configures the DNS lookups made by [`Impit`](crate::impit::Impit) - resolving the hosts to connect to and the `HTTPS` record lookups for HTTP/3 discovery.
+///
+/// ### Example
+/// ```rust
+/// let impit = Impit::builder()
+///   .with_http3()
+///   .with_dns(DnsConfig::NameServers(vec![
+///     NameServer::https("1.1.1.1:443".parse().unwrap(), "cloudflare-dns.com"),
+///   ]))
+///   .build();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum DnsConfig {
+  /// Use the nameservers from the system configuration (`/etc/resolv.conf` on Unix).
+  ///
+  /// If the system configuration can't be read, the hosts are resolved by the operating system (`getaddrinfo`)
+  /// and the `HTTPS` record lookups fail (HTTP/3 is then only discovered from the `Alt-Svc` headers).
+  #[default]
+  System,
+  /// Use the given nameservers, in order.
+  NameServers(Vec<NameServer>),
+}
+
+impl DnsConfig {
+  /// Creates the `hickory` resolver described by the configuration, or `None` if the system configuration can't be read.
+  fn resolver(&self) -> Option<TokioAsyncResolver> {
+    let (config, options) = match self {
+      DnsConfig::System => match hickory_resolver::system_conf::read_system_conf() {
+        Ok(system_conf) => system_conf,
+        Err(err) => {
+          debug!("Couldn't read the system DNS configuration ({}), falling back to the system resolver", err);
+          return None;
+        },
+      },
+      DnsConfig::NameServers(name_servers) => {
+        let mut config = ResolverConfig::new();
+
+        for name_server in name_servers {
+          let protocol = match name_server.protocol {
+            DnsProtocol::Udp => Protocol::Udp,
+            DnsProtocol::Tcp => Protocol::Tcp,
+            DnsProtocol::Https => Protocol::Https,
+            DnsProtocol::Tls => Protocol::Tls,
+          };
+
+          let mut name_server_config = NameServerConfig::new(name_server.address, protocol);
+          name_server_config.tls_dns_name = name_server.tls_name.clone();
+          config.add_name_server(name_server_config);
+        }
+
+        (config, ResolverOpts::default())
+      },
+    };
+
+    Some(TokioAsyncResolver::tokio(config, options))
+  }
+}
+
+/// The default [`Resolver`], using the `hickory` DNS client with the nameservers from [`DnsConfig`].
+pub struct HickoryResolver {
+  /// `None` if the system configuration couldn't be read.
+  resolver: Option<TokioAsyncResolver>,
+}
+
+impl HickoryResolver {
+  pub fn new(config: &DnsConfig) -> Self {
+    HickoryResolver { resolver: config.resolver() }
+  }
+}
+
+impl Resolver for HickoryResolver {
+  fn lookup_ip<'a>(&'a self, host: &'a str) -> DnsFuture<'a, IpAddr> {
+    Box::pin(async move {
+      let Some(resolver) = &self.resolver else {
+        // `getaddrinfo` doesn't report the TTL, the answers are cached by the system (if at all).
+        let addresses = tokio::net::lookup_host((host, 0)).await?;
+        return Ok(DnsAnswer { records: addresses.map(|address| address.ip()).collect(), ttl: Duration::ZERO });
+      };
+
+      let lookup = resolver.lookup_ip(host).await?;
+
+      Ok(DnsAnswer {
+        records: lookup.iter().collect(),
+        ttl: lookup.valid_until().saturating_duration_since(Instant::now()),
+      })
+    })
+  }
+
+  fn lookup_https<'a>(&'a self, host: &'a str) -> DnsFuture<'a, HttpsRecord> {
+    Box::pin(async move {
+      let resolver = self.resolver.as_ref().ok_or("the system DNS configuration couldn't be read")?;
+
+      let lookup = match resolver.lookup(host, RecordType::HTTPS).await {
+        Ok(lookup) => lookup,
+        // Most hosts have no `HTTPS` records, which is a valid (cacheable) answer.
+        Err(err) => match err.kind() {
+          ResolveErrorKind::NoRecordsFound { negative_ttl, .. } => return Ok(DnsAnswer {
+            records: vec![],
+            ttl: negative_ttl.map(|ttl| Duration::from_secs(ttl.into())).unwrap_or(DEFAULT_NEGATIVE_TTL),
+          }),
+          _ => return Err(err.into()),
+        },
+      };
+
+      let records = lookup.iter()
+        .filter_map(|record| match record {
+          RData::HTTPS(https) => Some(https_record(https)),
+          _ => None,
+        })
+        .collect();
+
+      Ok(DnsAnswer {
+        records,
+        ttl: lookup.valid_until().saturating_duration_since(Instant::now()),
+      })
+    })
+  }
+}
+
+fn https_record(https: &SVCB) -> HttpsRecord {
+  let mut record = HttpsRecord {
+    priority: https.svc_priority(),
+    target: https.target_name().to_ascii().trim_end_matches('.').to_string(),
+    ..HttpsRecord::default()
+  };
+
+  for (_, value) in https.svc_params() {
+    match value {
+      SvcParamValue::Alpn(alpn) => record.alpn.extend(alpn.0.iter().cloned()),
+      SvcParamValue::Port(port) => record.port = Some(*port),
+      SvcParamValue::Ipv4Hint(hint) => record.ipv4_hints.extend(hint.0.iter().map(|address| address.0)),
+      SvcParamValue::Ipv6Hint(hint) => record.ipv6_hints.extend(hint.0.iter().map(|address| address.0)),
+      SvcParamValue::EchConfig(ech_config) => record.ech_config = Some(ech_config.0.clone()),
+      _ => {},
+    }
+  }
+
+  record
+}
+
+struct CachedAnswer<T> {
+  records: Vec<T>,
+  expires: Instant,
+}
+
+type AnswerCache<T> = Arc<Mutex<HashMap<String, CachedAnswer<T>>>>;
+
+/// Where the HTTP/3 connections to an origin go (see [`ImpitResolver::set_h3_alternative`]).
+#[derive(Clone, PartialEq, Eq)]
+struct H3Alternative {
+  host: String,
+  /// The addresses from the `HTTPS` record hints, used if the host doesn't resolve.
+  ip_hints: Vec<IpAddr>,
+}
+
+/// The resolver used by all the connections and lookups of an [`Impit`](crate::impit::Impit) instance.
+///
+/// Applies the static host overrides and caches the answers of the configured [`Resolver`] for their TTL.
+/// Cheap to clone, the clones share the cache.
+#[derive(Clone)]
+pub(crate) struct ImpitResolver {
+  resolver: Arc<dyn Resolver>,
+  overrides: Arc<HashMap<String, Vec<IpAddr>>>,
+  ip_cache: AnswerCache<IpAddr>,
+  https_cache: AnswerCache<HttpsRecord>,
+  /// The hosts the HTTP/3 connections go to, per origin host and port (see [`ImpitResolver::set_h3_alternative`]).
+  h3_alternatives: Arc<Mutex<HashMap<String, HashMap<u16, H3Alternative>>>>,
+  /// Whether the resolver is used by the HTTP/3 client, i.e. resolves the hosts to their alternative services.
+  h3: bool,
+}
+
+impl ImpitResolver {
+  pub(crate) fn new(resolver: Arc<dyn Resolver>, overrides: HashMap<String, Vec<IpAddr>>) -> Self {
+    ImpitResolver {
+      resolver,
+      overrides: Arc::new(overrides.into_iter().map(|(host, addresses)| (host.to_ascii_lowercase(), addresses)).collect()),
+      ip_cache: Arc::new(Mutex::new(HashMap::new())),
+      https_cache: Arc::new(Mutex::new(HashMap::new())),
+      h3_alternatives: Arc::new(Mutex::new(HashMap::new())),
+      h3: false,
+    }
+  }
+
+  /// Returns the resolver for the HTTP/3 client, connecting to the alternative hosts of the HTTP/3 services.
+  pub(crate) fn for_h3(&self) -> Self {
+    ImpitResolver { h3: true, ..self.clone() }
+  }
+
+  /// Makes the HTTP/3 connections to the origin (`host` and `port`) go to the `alternative` host (advertised by `Alt-Svc` or the `HTTPS` records),
+  /// or to `host` itself for `None`. The `ip_hints` are used if the host doesn't resolve.
+  ///
+  /// Called when the HTTP/3 endpoint of the origin is discovered, [`ImpitResolver::remove_h3_alternative`] is called when the origin loses HTTP/3 support.
+  /// The TLS handshake and the `:authority` still use the origin's host.
+  pub(crate) fn set_h3_alternative(&self, host: &str, port: u16, alternative: Option<&str>, ip_hints: &[IpAddr]) {
+    let host = host.to_ascii_lowercase();
+    let alternative = H3Alternative {
+      host: alternative.map(|alternative| alternative.to_ascii_lowercase()).unwrap_or_else(|| host.clone()),
+      ip_hints: ip_hints.to_vec(),
+    };
+
+    self.h3_alternatives.lock().unwrap()
+      .entry(host)
+      .or_default()
+      .insert(port, alternative);
+  }
+
+  /// Forgets the HTTP/3 alternative of the origin, see [`ImpitResolver::set_h3_alternative`].
+  pub(crate) fn remove_h3_alternative(&self, host: &str, port: u16) {
+    let mut h3_alternatives = self.h3_alternatives.lock().unwrap();
+    let host = host.to_ascii_lowercase();
+
+    if let Some(ports) = h3_alternatives.get_mut(&host) {
+      ports.remove(&port);
+
+      if ports.is_empty() {
+        h3_alternatives.remove(&host);
+      }
+    }
+  }
+
+  /// Tells whether the HTTP/3 origins of the host on different ports go to different alternatives.
+  ///
+  /// The connector only passes the host name to the resolver, so the connections to such origins can't be told apart.
+  pub(crate) fn h3_alternative_is_ambiguous(&self, host: &str) -> bool {
+    self.h3_alternative(host).is_err()
+  }
+
+  /// Returns the alternative the HTTP/3 connections to the host go to, `None` if the host has no alternative,
+  /// or an error if its origins disagree on the alternative.
+  fn h3_alternative(&self, host: &str) -> Result<Option<H3Alternative>, BoxError> {
+    let h3_alternatives = self.h3_alternatives.lock().unwrap();
+    let Some(ports) = h3_alternatives.get(&host.to_ascii_lowercase()) else {
+      return Ok(None);
+    };
+
+    let mut alternatives = ports.values();
+    let first = alternatives.next().cloned();
+
+    match alternatives.all(|alternative| Some(alternative) == first.as_ref()) {
+      true => Ok(first),
+      false => Err(format!("the HTTP/3 origins of {} have different alternatives", host).into()),
+    }
+  }
+
+  pub(crate) async fn lookup_ip(&self, host: &str) -> Result<Vec<IpAddr>, BoxError> {
+    let host = host.to_ascii_lowercase();
+
+    if let Some(addresses) = self.overrides.get(&host) {
+      return Ok(addresses.clone());
+    }
+
+    if let Some(answer) = cached(&self.ip_cache, &host) {
+      return Ok(answer.records);
+    }
+
+    let answer = self.resolver.lookup_ip(&host).await?;
+    store(&self.ip_cache, host, &answer);
+
+    Ok(answer.records)
+  }
+
+  /// Returns the `HTTPS` records of the host, with the remaining TTL of the cached answers.
+  pub(crate) async fn lookup_https(&self, host: &str) -> Result<DnsAnswer<HttpsRecord>, BoxError> {
+    let host = host.to_ascii_lowercase();
+
+    if let Some(answer) = cached(&self.https_cache, &host) {
+      return Ok(answer);
+    }
+
+    let answer = self.resolver.lookup_https(&host).await?;
+    store(&self.https_cache, host, &answer);
+
+    Ok(answer)
+  }
+}
+
+fn cached<T: Clone>(cache: &AnswerCache<T>, host: &str) -> Option<DnsAnswer<T>> {
+  let cache = cache.lock().unwrap();
+  let now = Instant::now();
+
+  cache.get(host)
+    .filter(|answer| answer.expires > now)
+    .map(|answer| DnsAnswer { records: answer.records.clone(), ttl: answer.expires - now })
+}
+
+fn store<T: Clone>(cache: &AnswerCache<T>, host: String, answer: &DnsAnswer<T>) {
+  if answer.ttl.is_zero() {
+    return;
+  }
+
+  cache.lock().unwrap().insert(host, CachedAnswer {
+    records: answer.records.clone(),
+    expires: expiry(Instant::now(), answer.ttl),
+  });
+}
+
+/// Returns when an answer received at `now` expires. The TTL is capped at [`MAX_TTL`], so that e.g. `Duration::MAX` doesn't overflow.
+pub(crate) fn expiry(now: Instant, ttl: Duration) -> Instant {
+  now.checked_add(ttl.min(MAX_TTL)).unwrap_or(now)
+}
+
+impl Resolve for ImpitResolver {
+  fn resolve(&self, name: Name) -> Resolving {
+    let resolver = self.clone();
+
+    Box::pin(async move {
+      // The HTTP/3 connections go to the alternative service, if there is one.
+      let alternative = match resolver.h3 {
+        true => resolver.h3_alternative(name.as_str())?,
+        false => None,
+      };
+      let H3Alternative { host, ip_hints } = alternative.unwrap_or_else(|| H3Alternative { host: name.as_str().to_string(), ip_hints: vec![] });
+
+      let addresses = match host.parse::<IpAddr>() {
+        Ok(address) => vec![address],
+        Err(_) => match resolver.lookup_ip(&host).await {
+          Ok(addresses) if !addresses.is_empty() => addresses,
+          // The hints are only used if the target doesn't resolve (RFC 9460, section 7.3).
+          _ if !ip_hints.is_empty() => {
+            debug!("{} doesn't resolve, using the HTTPS record hints {:?}", host, ip_hints);
+            ip_hints
+          },
+          result => result?,
+        },
+      };
+
+      // The port is filled in by the connector.
+      let addresses: Addrs = Box::new(addresses.into_iter().map(|address| SocketAddr::new(address, 0)));
+
+      Ok(addresses)
+    })
+  }
+}
@@ -0,0 +1,86 @@
+mod profile;
+mod registry;
+
+pub use profile::{BrowserFamily, BrowserProfile, ContextHeaders, ProfileError, TlsProfile};
+pub use crate::http2::{Http2Fingerprint, Http2Setting, PriorityFrame, StreamPriority};
+pub use crate::tls::{ClientHello, ClientHelloError, TlsFingerprint};
+
+/// Supported releases of the Chromium-based browsers (Chrome, Edge, Chrome on Android).
+///
+/// Edge follows the Chromium release numbering, so the same versions are used for all of these browsers.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy, Default)]
+pub enum ChromeVersion {
+  V125,
+  #[default]
+  V131,
+}
+
+/// Supported releases of Firefox.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy, Default)]
+pub enum FirefoxVersion {
+  V128,
+  #[default]
+  V133,
+}
+
+/// Supported releases of Safari (both macOS and iOS).
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy, Default)]
+pub enum SafariVersion {
+  V17,
+  #[default]
+  V18,
+}
+
+/// The `Browser` enum is used to specify the browser that should be impersonated.
+///
+/// The variants are presets of [`BrowserProfile`]s. To impersonate a browser that isn't supported out of the box,
+/// use a custom profile instead.
+///
+/// Every variant carries the browser release, so the impersonation (TLS, HTTP/2 and HTTP headers) is pinned to a specific version.
+/// The `Default` implementations of the version enums point to the latest supported release.
+///
+/// It can be passed as a parameter to [`ImpitBuilder::with_browser`](crate::impit::ImpitBuilder::with_browser)
+/// to use the browser emulation with the built [`Impit`](crate::impit::Impit) instance.
+///
+/// ### Example
+/// ```rust
+/// let pinned = Browser::Chrome(ChromeVersion::V125);
+/// let latest = Browser::Firefox(FirefoxVersion::default());
+/// ```
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum Browser {
+  Chrome(ChromeVersion),
+  Firefox(FirefoxVersion),
+  /// Microsoft Edge on Windows.
+  Edge(ChromeVersion),
+  /// Chrome on Android (mobile).
+  ChromeAndroid(ChromeVersion),
+  /// Safari on macOS.
+  Safari(SafariVersion),
+  /// Safari on iOS (mobile).
+  SafariIos(SafariVersion),
+}
+
+impl Default for Browser {
+  fn default() -> Self {
+    Browser::Chrome(ChromeVersion::default())
+  }
+}
+
+impl Browser {
+  /// Returns the HTTP/2 connection-level fingerprint (`SETTINGS`, `WINDOW_UPDATE`, `PRIORITY` frames and pseudo-header order) of the browser.
+  pub fn http2_fingerprint(&self) -> Http2Fingerprint {
+    self.profile().http2
+  }
+
+  /// Returns the complete profile (TLS, HTTP/2 and HTTP headers) of the browser release.
+  pub fn profile(&self) -> BrowserProfile {
+    registry::profile(self)
+  }
+}
+
+impl From<Browser> for BrowserProfile {
+  fn from(browser: Browser) -> Self {
+    browser.profile()
+  }
+}
@@ -0,0 +1,254 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use reqwest::header::{HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
+
+use crate::http2::Http2Fingerprint;
+use crate::request::RequestContext;
+use crate::tls;
+
+/// Error types that can be returned when loading a [`BrowserProfile`].
+#[derive(Debug)]
+pub enum ProfileError {
+  /// The profile file couldn't be read.
+  IoError(std::io::Error),
+  /// The profile isn't a valid JSON profile description.
+  JsonError(serde_json::Error),
+  /// The profile isn't a valid TOML profile description.
+  TomlError(toml::de::Error),
+  /// A header can't be sent in an HTTP request, e.g. its name contains a space or its value contains a newline.
+  InvalidHeader {
+    name: String,
+    value: String,
+  },
+  /// The cipher suite (IANA identifier) isn't implemented by `rustls`.
+  UnsupportedCipherSuite(u16),
+  /// The named group (IANA identifier) isn't supported for the key exchange.
+  UnsupportedGroup(u16),
+  /// The TLS profile option (e.g. `signature_algorithms`) can't be applied by the `rustls` emulator, it has to be left empty.
+  UnsupportedTlsOption(&'static str),
+}
+
+impl std::fmt::Display for ProfileError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ProfileError::IoError(err) => write!(f, "the profile file couldn't be read: {}", err),
+      ProfileError::JsonError(err) => write!(f, "invalid JSON profile: {}", err),
+      ProfileError::TomlError(err) => write!(f, "invalid TOML profile: {}", err),
+      ProfileError::InvalidHeader { name, value } => write!(f, "invalid header {:?}: {:?}", name, value),
+      ProfileError::UnsupportedCipherSuite(id) => write!(f, "unsupported cipher suite {:#06x}", id),
+      ProfileError::UnsupportedGroup(id) => write!(f, "unsupported group {:#06x}", id),
+      ProfileError::UnsupportedTlsOption(option) => write!(f, "the {} TLS option isn't supported, leave it empty", option),
+    }
+  }
+}
+
+impl std::error::Error for ProfileError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      ProfileError::IoError(err) => Some(err),
+      ProfileError::JsonError(err) => Some(err),
+      ProfileError::TomlError(err) => Some(err),
+      _ => None,
+    }
+  }
+}
+
+/// The browser family whose TLS stack the ClientHello emulation is based on.
+///
+/// The family decides the parts of the ClientHello that can't be described by lists of identifiers,
+/// e.g. GREASE values, padding or the contents of the extensions.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BrowserFamily {
+  Chrome,
+  Firefox,
+  Safari,
+}
+
+/// Describes the TLS ClientHello of a browser.
+///
+/// All the lists contain the IANA identifiers of the respective values, in the order they're sent.
+/// Empty lists keep the defaults of the emulated browser family.
+///
+/// The `rustls` emulator only takes the cipher suites, the groups and ALPN from the profile.
+/// The key shares, signature algorithms and the extension order are the emulator defaults of the browser release,
+/// the profiles listing them are rejected by [`BrowserProfile::validate`].
+///
+/// A profile can be created from a captured handshake of a real browser with [`ClientHello::to_tls_profile`](crate::emulation::ClientHello::to_tls_profile).
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
+pub struct TlsProfile {
+  /// The browser family the emulation is based on.
+  pub family: BrowserFamily,
+  /// The release of the browser family the emulation is based on.
+  pub version: u16,
+  #[serde(default)]
+  pub cipher_suites: Vec<u16>,
+  /// Named groups offered in the `supported_groups` extension.
+  #[serde(default)]
+  pub supported_groups: Vec<u16>,
+  /// Named groups the client sends key shares for. Not supported yet, must be empty.
+  #[serde(default)]
+  pub key_shares: Vec<u16>,
+  /// Not supported yet, must be empty.
+  #[serde(default)]
+  pub signature_algorithms: Vec<u16>,
+  /// Not supported yet, must be empty.
+  #[serde(default)]
+  pub extensions_order: Vec<u16>,
+  /// ALPN protocols (e.g. `h2`, `http/1.1`). Replaced by `h3` for HTTP/3 connections.
+  #[serde(default)]
+  pub alpn: Vec<String>,
+}
+
+/// Changes of the default headers in a [`RequestContext`] other than the top-level navigation.
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContextHeaders {
+  /// New values of the default headers (e.g. `accept` or `sec-fetch-dest`). The headers keep their position.
+  /// 
+  /// Header names are matched case-insensitively. Headers the browser doesn't send by default are ignored.
+  #[serde(default)]
+  pub headers: Vec<(String, String)>,
+  /// Default headers that aren't sent in this context (e.g. `sec-fetch-user`).
+  #[serde(default)]
+  pub removed_headers: Vec<String>,
+}
+
+/// A complete description of an impersonated browser - TLS ClientHello, HTTP/2 fingerprint and the default HTTP headers.
+///
+/// The built-in [`Browser`](crate::emulation::Browser) presets are converted to this type internally,
+/// so a custom profile has the same capabilities as the built-in ones.
+/// Profiles can be loaded from JSON or TOML files and passed to [`ImpitBuilder::with_browser_profile`](crate::impit::ImpitBuilder::with_browser_profile).
+///
+/// ### Example
+/// ```toml
+/// name = "Chrome 131 (custom)"
+/// headers = [
+///   ["user-agent", "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36"],
+///   ["accept", "*/*"],
+/// ]
+/// http1_headers = [
+///   ["Host", ""],
+///   ["Connection", "keep-alive"],
+///   ["User-Agent", "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36"],
+///   ["Accept", "*/*"],
+/// ]
+/// client_hints = [["sec-ch-ua-arch", "\"x86\""], ["sec-ch-ua-bitness", "\"64\""]]
+///
+/// [contexts.fetch]
+/// headers = [["accept", "*/*"], ["sec-fetch-mode", "cors"], ["sec-fetch-dest", "empty"]]
+/// removed_headers = ["upgrade-insecure-requests", "sec-fetch-user"]
+///
+/// [tls]
+/// family = "chrome"
+/// version = 131
+/// alpn = ["h2", "http/1.1"]
+///
+/// [http2]
+/// settings = [["HEADER_TABLE_SIZE", 65536], ["ENABLE_PUSH", 0], ["INITIAL_WINDOW_SIZE", 6291456], ["MAX_HEADER_LIST_SIZE", 262144]]
+/// window_update_increment = 15663105
+/// priority_frames = []
+/// headers_priority = { stream_dependency = 0, exclusive = true, weight = 256 }
+/// pseudo_headers_order = [":method", ":authority", ":scheme", ":path"]
+/// ```
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
+pub struct BrowserProfile {
+  /// Human-readable name of the profile.
+  pub name: String,
+  /// The default HTTP headers, in the order the browser sends them over HTTP/2 (and HTTP/3).
+  pub headers: Vec<(String, String)>,
+  /// The default HTTP headers sent over HTTP/1.1, in the order and letter case the browser sends them.
+  ///
+  /// The value of the `Host` header is filled in for every request. If empty, `headers` are used for HTTP/1.1 as well.
+  #[serde(default)]
+  pub http1_headers: Vec<(String, String)>,
+  /// Header changes for the non-navigation requests. The default headers describe a top-level navigation.
+  #[serde(default)]
+  pub contexts: BTreeMap<RequestContext, ContextHeaders>,
+  /// The high-entropy client hints (e.g. `sec-ch-ua-full-version-list`) the browser sends to the origins requesting them with `Accept-CH`.
+  ///
+  /// Only Chromium-based browsers support client hints, the list is empty for the others.
+  #[serde(default)]
+  pub client_hints: Vec<(String, String)>,
+  pub tls: TlsProfile,
+  pub http2: Http2Fingerprint,
+}
+
+impl BrowserProfile {
+  /// Parses a profile from its JSON description.
+  ///
+  /// The parsed profile is checked with [`BrowserProfile::validate`].
+  pub fn from_json(json: &str) -> Result<Self, ProfileError> {
+    let profile: Self = serde_json::from_str(json).map_err(ProfileError::JsonError)?;
+    profile.validate()?;
+    Ok(profile)
+  }
+
+  /// Parses a profile from its TOML description.
+  ///
+  /// The parsed profile is checked with [`BrowserProfile::validate`].
+  pub fn from_toml(toml: &str) -> Result<Self, ProfileError> {
+    let profile: Self = toml::from_str(toml).map_err(ProfileError::TomlError)?;
+    profile.validate()?;
+    Ok(profile)
+  }
+
+  /// Checks that the profile can be used for requests - all the headers are valid HTTP headers,
+  /// all the cipher suites and groups are supported and the TLS options the emulator can't apply are empty.
+  ///
+  /// The loaded profiles are checked automatically, the profiles built in code are checked by
+  /// [`ImpitBuilder::try_build`](crate::impit::ImpitBuilder::try_build).
+  pub fn validate(&self) -> Result<(), ProfileError> {
+    let context_headers = self.contexts.values().flat_map(|context| context.headers.iter());
+
+    let headers = self.headers.iter()
+      .chain(self.http1_headers.iter())
+      .chain(context_headers)
+      .chain(self.client_hints.iter());
+
+    for (name, value) in headers {
+      if HeaderName::from_bytes(name.as_bytes()).is_err() || HeaderValue::from_str(value).is_err() {
+        return Err(ProfileError::InvalidHeader { name: name.to_owned(), value: value.to_owned() });
+      }
+    }
+
+    if let Some(id) = tls::unsupported_cipher_suite(&self.tls.cipher_suites) {
+      return Err(ProfileError::UnsupportedCipherSuite(id));
+    }
+
+    if let Some(id) = tls::unsupported_group(&self.tls.supported_groups) {
+      return Err(ProfileError::UnsupportedGroup(id));
+    }
+
+    let unsupported_options = [
+      ("key_shares", &self.tls.key_shares),
+      ("signature_algorithms", &self.tls.signature_algorithms),
+      ("extensions_order", &self.tls.extensions_order),
+    ];
+
+    if let Some((option, _)) = unsupported_options.iter().find(|(_, values)| !values.is_empty()) {
+      return Err(ProfileError::UnsupportedTlsOption(*option));
+    }
+
+    Ok(())
+  }
+
+  /// Loads a profile from a file.
+  ///
+  /// Files with the `.toml` extension are parsed as TOML, everything else is parsed as JSON.
+  pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ProfileError> {
+    let path = path.as_ref();
+    let contents = std::fs::read_to_string(path).map_err(ProfileError::IoError)?;
+
+    match path.extension().and_then(|extension| extension.to_str()) {
+      Some("toml") => Self::from_toml(&contents),
+      _ => Self::from_json(&contents),
+    }
+  }
+
+  /// Serializes the profile to JSON, e.g. to use a built-in preset as a starting point for a custom profile.
+  pub fn to_json(&self) -> String {
+    serde_json::to_string_pretty(self).unwrap()
+  }
+}
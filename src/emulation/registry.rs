@@ -0,0 +1,277 @@
+use std::collections::BTreeMap;
+
+use super::{Browser, BrowserFamily, BrowserProfile, ChromeVersion, ContextHeaders, FirefoxVersion, SafariVersion, TlsProfile};
+use crate::http2::Http2Fingerprint;
+use crate::http_headers::statics;
+use crate::request::RequestContext;
+
+/// Firefox also offers the finite field Diffie-Hellman groups (`ffdhe2048`, `ffdhe3072`).
+const FIREFOX_SUPPORTED_GROUPS: &[u16] = &[0x001d, 0x0017, 0x0018, 0x0100, 0x0101];
+
+const ALPN_H2: &[&str] = &["h2", "http/1.1"];
+
+/// Builds the preset profile of a built-in browser release.
+///
+/// New browser releases are added by extending this function - the TLS, HTTP/2 and header profiles
+/// of a release should always be updated together, so they don't disagree with each other.
+pub(crate) fn profile(browser: &Browser) -> BrowserProfile {
+  match browser {
+    Browser::Chrome(version) => chromium("Chrome", *version, statics::CHROME_125_HEADERS, statics::CHROME_131_HEADERS),
+    Browser::Edge(version) => chromium("Edge", *version, statics::EDGE_125_HEADERS, statics::EDGE_131_HEADERS),
+    Browser::ChromeAndroid(version) => chromium("Chrome Android", *version, statics::CHROME_ANDROID_125_HEADERS, statics::CHROME_ANDROID_131_HEADERS),
+    Browser::Firefox(version) => {
+      let (headers, tls_version) = match version {
+        FirefoxVersion::V128 => (statics::FIREFOX_128_HEADERS, 128),
+        FirefoxVersion::V133 => (statics::FIREFOX_133_HEADERS, 133),
+      };
+
+      BrowserProfile {
+        name: format!("Firefox {}", tls_version),
+        headers: to_owned_headers(headers),
+        http1_headers: http1_headers(BrowserFamily::Firefox, headers),
+        contexts: contexts(BrowserFamily::Firefox),
+        client_hints: vec![],
+        tls: TlsProfile {
+          supported_groups: FIREFOX_SUPPORTED_GROUPS.to_vec(),
+          ..tls_profile(BrowserFamily::Firefox, tls_version)
+        },
+        http2: Http2Fingerprint::firefox(),
+      }
+    },
+    Browser::Safari(version) => {
+      let (headers, tls_version, http2) = match version {
+        SafariVersion::V17 => (statics::SAFARI_17_HEADERS, 17, Http2Fingerprint::safari_17()),
+        SafariVersion::V18 => (statics::SAFARI_18_HEADERS, 18, Http2Fingerprint::safari_18()),
+      };
+
+      BrowserProfile {
+        name: format!("Safari {}", tls_version),
+        headers: to_owned_headers(headers),
+        http1_headers: http1_headers(BrowserFamily::Safari, headers),
+        contexts: contexts(BrowserFamily::Safari),
+        client_hints: vec![],
+        tls: tls_profile(BrowserFamily::Safari, tls_version),
+        http2,
+      }
+    },
+    Browser::SafariIos(version) => {
+      let (headers, tls_version, http2) = match version {
+        SafariVersion::V17 => (statics::SAFARI_IOS_17_HEADERS, 17, Http2Fingerprint::safari_ios_17()),
+        SafariVersion::V18 => (statics::SAFARI_IOS_18_HEADERS, 18, Http2Fingerprint::safari_18()),
+      };
+
+      BrowserProfile {
+        name: format!("Safari iOS {}", tls_version),
+        headers: to_owned_headers(headers),
+        http1_headers: http1_headers(BrowserFamily::Safari, headers),
+        contexts: contexts(BrowserFamily::Safari),
+        client_hints: vec![],
+        tls: tls_profile(BrowserFamily::Safari, tls_version),
+        http2,
+      }
+    },
+  }
+}
+
+/// Chromium-based browsers share the TLS stack (BoringSSL) and the HTTP/2 implementation, they only differ in the headers.
+fn chromium(
+  name: &str,
+  version: ChromeVersion,
+  v125_headers: &'static [(&'static str, &'static str)],
+  v131_headers: &'static [(&'static str, &'static str)],
+) -> BrowserProfile {
+  let (headers, tls_version) = match version {
+    ChromeVersion::V125 => (v125_headers, 125),
+    ChromeVersion::V131 => (v131_headers, 131),
+  };
+
+  BrowserProfile {
+    name: format!("{} {}", name, tls_version),
+    headers: to_owned_headers(headers),
+    http1_headers: http1_headers(BrowserFamily::Chrome, headers),
+    contexts: contexts(BrowserFamily::Chrome),
+    client_hints: client_hints(name, version),
+    tls: tls_profile(BrowserFamily::Chrome, tls_version),
+    http2: Http2Fingerprint::chrome(),
+  }
+}
+
+/// The high-entropy client hints of a Chromium-based browser, consistent with its `sec-ch-ua` and `user-agent` headers.
+fn client_hints(name: &str, version: ChromeVersion) -> Vec<(String, String)> {
+  let (chromium_version, edge_version, grease_brand) = match version {
+    ChromeVersion::V125 => ("125.0.6422.141", "125.0.2535.92", "Not.A/Brand"),
+    ChromeVersion::V131 => ("131.0.6778.85", "131.0.2903.86", "Not_A Brand"),
+  };
+
+  let (brand, brand_version) = match name {
+    "Edge" => ("Microsoft Edge", edge_version),
+    _ => ("Google Chrome", chromium_version),
+  };
+
+  let (arch, bitness, model, platform_version, form_factor) = match name {
+    "Edge" => ("x86", "64", "", "15.0.0", "Desktop"),
+    "Chrome Android" => ("", "", "Pixel 7", "14.0.0", "Mobile"),
+    _ => ("x86", "64", "", "6.8.0", "Desktop"),
+  };
+
+  let mut hints = vec![
+    ("sec-ch-ua-arch", format!("\"{}\"", arch)),
+    ("sec-ch-ua-bitness", format!("\"{}\"", bitness)),
+    ("sec-ch-ua-full-version", format!("\"{}\"", brand_version)),
+    ("sec-ch-ua-full-version-list", format!("\"{}\";v=\"{}\", \"Chromium\";v=\"{}\", \"{}\";v=\"24.0.0.0\"", brand, brand_version, chromium_version, grease_brand)),
+    ("sec-ch-ua-model", format!("\"{}\"", model)),
+    ("sec-ch-ua-platform-version", format!("\"{}\"", platform_version)),
+    ("sec-ch-ua-wow64", String::from("?0")),
+  ];
+
+  // `Sec-CH-UA-Form-Factors` shipped in Chrome 127.
+  if version == ChromeVersion::V131 {
+    hints.push(("sec-ch-ua-form-factors", format!("\"{}\"", form_factor)));
+  }
+
+  hints.into_iter().map(|(name, value)| (name.to_string(), value)).collect()
+}
+
+/// The presets rely on the defaults of the `rustls` browser emulator for everything but ALPN, unless they override more (Firefox).
+fn tls_profile(family: BrowserFamily, version: u16) -> TlsProfile {
+  TlsProfile {
+    family,
+    version,
+    cipher_suites: vec![],
+    supported_groups: vec![],
+    key_shares: vec![],
+    signature_algorithms: vec![],
+    extensions_order: vec![],
+    alpn: ALPN_H2.iter().map(|protocol| protocol.to_string()).collect(),
+  }
+}
+
+/// `Accept`, `Sec-Fetch-Mode`, `Sec-Fetch-Dest` and `Priority` values of the non-navigation requests, per browser family.
+///
+/// The `Priority` values only apply to the releases that send the header.
+fn context_values(family: BrowserFamily, context: RequestContext) -> (&'static str, &'static str, &'static str, &'static str) {
+  match (family, context) {
+    (BrowserFamily::Chrome, RequestContext::Fetch) => ("*/*", "cors", "empty", "u=1, i"),
+    (BrowserFamily::Chrome, RequestContext::Image) => ("image/avif,image/webp,image/apng,image/svg+xml,image/*,*/*;q=0.8", "no-cors", "image", "i"),
+    (BrowserFamily::Chrome, RequestContext::Script) => ("*/*", "no-cors", "script", "u=1"),
+    (BrowserFamily::Chrome, RequestContext::Style) => ("text/css,*/*;q=0.1", "no-cors", "style", "u=0"),
+    (BrowserFamily::Chrome, RequestContext::Font) => ("*/*", "cors", "font", "u=0"),
+
+    (BrowserFamily::Firefox, RequestContext::Fetch) => ("*/*", "cors", "empty", "u=4"),
+    (BrowserFamily::Firefox, RequestContext::Image) => ("image/avif,image/webp,image/png,image/svg+xml,image/*;q=0.8,*/*;q=0.5", "no-cors", "image", "u=5, i"),
+    (BrowserFamily::Firefox, RequestContext::Script) => ("*/*", "no-cors", "script", "u=2"),
+    (BrowserFamily::Firefox, RequestContext::Style) => ("text/css,*/*;q=0.1", "no-cors", "style", "u=2"),
+    (BrowserFamily::Firefox, RequestContext::Font) => ("application/font-woff2;q=1.0,application/font-woff;q=0.9,*/*;q=0.8", "cors", "font", "u=3"),
+
+    (BrowserFamily::Safari, RequestContext::Fetch) => ("*/*", "cors", "empty", "u=3, i"),
+    (BrowserFamily::Safari, RequestContext::Image) => ("image/webp,image/avif,image/jxl,image/heic,image/heic-sequence,video/*;q=0.8,image/png,image/svg+xml,image/*;q=0.8,*/*;q=0.5", "no-cors", "image", "u=5, i"),
+    (BrowserFamily::Safari, RequestContext::Script) => ("*/*", "no-cors", "script", "u=2"),
+    (BrowserFamily::Safari, RequestContext::Style) => ("text/css,*/*;q=0.1", "no-cors", "style", "u=2"),
+    (BrowserFamily::Safari, RequestContext::Font) => ("*/*", "cors", "font", "u=3"),
+
+    (BrowserFamily::Chrome, _) => ("", "navigate", "iframe", "u=0, i"),
+    (BrowserFamily::Firefox, _) => ("", "navigate", "iframe", "u=4"),
+    (BrowserFamily::Safari, _) => ("", "navigate", "iframe", "u=3, i"),
+  }
+}
+
+/// Builds the header changes of the non-navigation contexts.
+///
+/// Subresources are never user-initiated, so they don't send `sec-fetch-user`, and only navigations send `upgrade-insecure-requests`.
+/// Iframes keep the navigation `Accept` header.
+///
+/// Unlike top-level navigations (`none`), these requests always come from a page. Without a known referrer,
+/// the CORS requests (`fetch()` and fonts) are `same-origin` - a cross-origin one would need the `Origin` of the page -
+/// and the other ones `cross-site`, the value that tells the server the least about the initiator.
+fn contexts(family: BrowserFamily) -> BTreeMap<RequestContext, ContextHeaders> {
+  let subresources = [
+    RequestContext::Fetch,
+    RequestContext::Image,
+    RequestContext::Script,
+    RequestContext::Style,
+    RequestContext::Font,
+    RequestContext::Iframe,
+  ];
+
+  subresources.into_iter()
+    .map(|context| {
+      let (accept, mode, dest, priority) = context_values(family, context);
+
+      let site = match mode {
+        "cors" => "same-origin",
+        _ => "cross-site",
+      };
+
+      let mut headers = vec![
+        (String::from("sec-fetch-site"), site.to_string()),
+        (String::from("sec-fetch-mode"), mode.to_string()),
+        (String::from("sec-fetch-dest"), dest.to_string()),
+        (String::from("priority"), priority.to_string()),
+      ];
+
+      let mut removed_headers = vec![String::from("sec-fetch-user")];
+
+      if context != RequestContext::Iframe {
+        headers.push((String::from("accept"), accept.to_string()));
+        removed_headers.push(String::from("upgrade-insecure-requests"));
+      }
+
+      (context, ContextHeaders { headers, removed_headers })
+    })
+    .collect()
+}
+
+/// Derives the HTTP/1.1 header set of a browser from its HTTP/2 headers.
+///
+/// Over HTTP/1.1, all the browsers send `Host` first and use title-cased header names (Chrome keeps the client hints lowercase).
+/// All of them add `Connection: keep-alive`, which is forbidden in HTTP/2 - Chrome right after `Host` (and it drops the `priority` header),
+/// Firefox right after `Accept-Encoding` and Safari last.
+fn http1_headers(family: BrowserFamily, headers: &[(&str, &str)]) -> Vec<(String, String)> {
+  let mut http1_headers = vec![(String::from("Host"), String::new())];
+
+  if family == BrowserFamily::Chrome {
+    http1_headers.push((String::from("Connection"), String::from("keep-alive")));
+  }
+
+  for (name, value) in headers {
+    if family == BrowserFamily::Chrome && name.eq_ignore_ascii_case("priority") {
+      continue;
+    }
+
+    let name = match family {
+      BrowserFamily::Chrome if name.starts_with("sec-ch-") => name.to_string(),
+      _ => title_case(name),
+    };
+
+    let is_accept_encoding = name.eq_ignore_ascii_case("accept-encoding");
+    http1_headers.push((name, value.to_string()));
+
+    if family == BrowserFamily::Firefox && is_accept_encoding {
+      http1_headers.push((String::from("Connection"), String::from("keep-alive")));
+    }
+  }
+
+  if family == BrowserFamily::Safari {
+    http1_headers.push((String::from("Connection"), String::from("keep-alive")));
+  }
+
+  http1_headers
+}
+
+/// Converts the header name to the title case, e.g. `sec-fetch-dest` to `Sec-Fetch-Dest`.
+fn title_case(name: &str) -> String {
+  name.split('-')
+    .map(|part| {
+      let mut chars = part.chars();
+      match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase(),
+        None => String::new(),
+      }
+    })
+    .collect::<Vec<_>>()
+    .join("-")
+}
+
+fn to_owned_headers(headers: &[(&str, &str)]) -> Vec<(String, String)> {
+  headers.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+}
@@ -0,0 +1,223 @@
+use reqwest::ClientBuilder;
+use serde::{Deserialize, Serialize};
+
+/// The initial flow-control window size defined by the HTTP/2 spec (RFC 9113, section 6.9.2).
+///
+/// The connection-level `WINDOW_UPDATE` increment is relative to this value.
+const DEFAULT_CONNECTION_WINDOW_SIZE: u32 = 65_535;
+
+/// HTTP/2 `SETTINGS` frame parameters.
+///
+/// See [RFC 9113, section 6.5.2](https://www.rfc-editor.org/rfc/rfc9113#section-6.5.2) for the meaning of the parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Http2Setting {
+  HeaderTableSize,
+  EnablePush,
+  MaxConcurrentStreams,
+  InitialWindowSize,
+  MaxFrameSize,
+  MaxHeaderListSize,
+  /// Signals that the client doesn't use the RFC 7540 priority scheme (RFC 9218, section 2.1).
+  NoRfc7540Priorities,
+}
+
+impl Http2Setting {
+  /// Returns the identifier of the setting, as sent on the wire.
+  pub fn id(&self) -> u16 {
+    match self {
+      Http2Setting::HeaderTableSize => 0x1,
+      Http2Setting::EnablePush => 0x2,
+      Http2Setting::MaxConcurrentStreams => 0x3,
+      Http2Setting::InitialWindowSize => 0x4,
+      Http2Setting::MaxFrameSize => 0x5,
+      Http2Setting::MaxHeaderListSize => 0x6,
+      Http2Setting::NoRfc7540Priorities => 0x9,
+    }
+  }
+}
+
+/// Stream priority information, as sent in the `PRIORITY` frames or the priority fields of the `HEADERS` frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreamPriority {
+  /// The stream this stream depends on.
+  pub stream_dependency: u32,
+  /// Whether the dependency is exclusive.
+  pub exclusive: bool,
+  /// The priority weight of the stream, between 1 and 256.
+  pub weight: u16,
+}
+
+/// A standalone `PRIORITY` frame sent by the client right after the connection preface.
+///
+/// Older Firefox versions used these to build a dependency tree of idle "placeholder" streams.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PriorityFrame {
+  /// The (idle) stream the priority is set for.
+  pub stream_id: u32,
+  pub priority: StreamPriority,
+}
+
+/// Describes the HTTP/2 connection-level behavior of a browser.
+///
+/// These are the values HTTP/2 fingerprinting techniques (e.g. the Akamai fingerprint) look at - the initial `SETTINGS` frame,
+/// the connection-level `WINDOW_UPDATE` increment, the `PRIORITY` frames and the pseudo-header order.
+/// All of them are applied to the connections, so the server sees the fingerprint described by [`Http2Fingerprint::akamai_string`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Http2Fingerprint {
+  /// The parameters of the initial `SETTINGS` frame, in the order they're sent.
+  pub settings: Vec<(Http2Setting, u32)>,
+  /// The increment of the connection-level `WINDOW_UPDATE` frame sent after the `SETTINGS` frame.
+  pub window_update_increment: u32,
+  /// `PRIORITY` frames sent after the connection preface.
+  pub priority_frames: Vec<PriorityFrame>,
+  /// Priority fields sent in the `HEADERS` frame of each request. `None` means the fields are omitted.
+  pub headers_priority: Option<StreamPriority>,
+  /// The order of the pseudo-headers (`:method`, `:path`...) in the `HEADERS` frame.
+  pub pseudo_headers_order: Vec<String>,
+}
+
+impl Http2Fingerprint {
+  pub fn chrome() -> Self {
+    Http2Fingerprint {
+      settings: vec![
+        (Http2Setting::HeaderTableSize, 65_536),
+        (Http2Setting::EnablePush, 0),
+        (Http2Setting::InitialWindowSize, 6_291_456),
+        (Http2Setting::MaxHeaderListSize, 262_144),
+      ],
+      window_update_increment: 15_663_105,
+      priority_frames: vec![],
+      headers_priority: Some(StreamPriority { stream_dependency: 0, exclusive: true, weight: 256 }),
+      pseudo_headers_order: to_strings(&[":method", ":authority", ":scheme", ":path"]),
+    }
+  }
+
+  pub fn firefox() -> Self {
+    Http2Fingerprint {
+      settings: vec![
+        (Http2Setting::HeaderTableSize, 65_536),
+        (Http2Setting::InitialWindowSize, 131_072),
+        (Http2Setting::MaxFrameSize, 16_384),
+      ],
+      window_update_increment: 12_517_377,
+      priority_frames: vec![],
+      headers_priority: Some(StreamPriority { stream_dependency: 0, exclusive: false, weight: 42 }),
+      pseudo_headers_order: to_strings(&[":method", ":path", ":authority", ":scheme"]),
+    }
+  }
+
+  pub fn safari_17() -> Self {
+    Http2Fingerprint {
+      settings: vec![
+        (Http2Setting::EnablePush, 0),
+        (Http2Setting::InitialWindowSize, 4_194_304),
+        (Http2Setting::MaxConcurrentStreams, 100),
+      ],
+      window_update_increment: 10_485_760,
+      priority_frames: vec![],
+      headers_priority: Some(StreamPriority { stream_dependency: 0, exclusive: false, weight: 255 }),
+      pseudo_headers_order: to_strings(&[":method", ":scheme", ":path", ":authority"]),
+    }
+  }
+
+  pub fn safari_ios_17() -> Self {
+    Http2Fingerprint {
+      settings: vec![
+        (Http2Setting::EnablePush, 0),
+        (Http2Setting::InitialWindowSize, 2_097_152),
+        (Http2Setting::MaxConcurrentStreams, 100),
+      ],
+      ..Self::safari_17()
+    }
+  }
+
+  /// Safari 18 uses the same HTTP/2 settings on macOS and iOS.
+  pub fn safari_18() -> Self {
+    Http2Fingerprint {
+      settings: vec![
+        (Http2Setting::EnablePush, 0),
+        (Http2Setting::MaxConcurrentStreams, 100),
+        (Http2Setting::InitialWindowSize, 2_097_152),
+        (Http2Setting::NoRfc7540Priorities, 1),
+      ],
+      window_update_increment: 10_420_225,
+      priority_frames: vec![],
+      headers_priority: None,
+      pseudo_headers_order: to_strings(&[":method", ":scheme", ":authority", ":path"]),
+    }
+  }
+
+  /// Renders the fingerprint in the Akamai format, i.e. `SETTINGS|WINDOW_UPDATE|PRIORITY|pseudo-header order`.
+  ///
+  /// ### Example
+  /// ```rust
+  /// let fingerprint = Browser::Chrome(ChromeVersion::V131).http2_fingerprint();
+  ///
+  /// assert_eq!(fingerprint.akamai_string(), "1:65536;2:0;4:6291456;6:262144|15663105|0|m,a,s,p");
+  /// ```
+  pub fn akamai_string(&self) -> String {
+    let settings = self.settings.iter()
+      .map(|(setting, value)| format!("{}:{}", setting.id(), value))
+      .collect::<Vec<_>>()
+      .join(";");
+
+    let priority_frames = if self.priority_frames.is_empty() {
+      String::from("0")
+    } else {
+      self.priority_frames.iter()
+        .map(|frame| format!(
+          "{}:{}:{}:{}",
+          frame.stream_id,
+          frame.priority.exclusive as u8,
+          frame.priority.stream_dependency,
+          frame.priority.weight
+        ))
+        .collect::<Vec<_>>()
+        .join(",")
+    };
+
+    let pseudo_headers = self.pseudo_headers_order.iter()
+      .map(|name| name.trim_start_matches(':').chars().next().unwrap_or_default().to_string())
+      .collect::<Vec<_>>()
+      .join(",");
+
+    format!("{}|{}|{}|{}", settings, self.window_update_increment, priority_frames, pseudo_headers)
+  }
+
+  /// Configures the `reqwest` client builder to send this fingerprint.
+  ///
+  /// The window and frame sizes map to the standard `reqwest` options, the rest (the other settings and their order,
+  /// the `PRIORITY` frames, the `HEADERS` frame priority and the pseudo-header order) relies on the patched `reqwest` and `h2` crates.
+  /// Only the listed settings are sent, in the listed order.
+  pub(crate) fn apply(&self, mut client: ClientBuilder) -> ClientBuilder {
+    client = client
+      .http2_settings_order(self.settings.iter().map(|(setting, _)| setting.id()).collect())
+      .http2_initial_connection_window_size(DEFAULT_CONNECTION_WINDOW_SIZE + self.window_update_increment)
+      .http2_priority_frames(self.priority_frames.iter().map(|frame| {
+        (frame.stream_id, frame.priority.stream_dependency, frame.priority.weight, frame.priority.exclusive)
+      }).collect())
+      .http2_headers_priority(self.headers_priority.map(|priority| {
+        (priority.stream_dependency, priority.weight, priority.exclusive)
+      }))
+      .http2_pseudo_header_order(self.pseudo_headers_order.clone());
+
+    for (setting, value) in self.settings.iter() {
+      client = match setting {
+        Http2Setting::HeaderTableSize => client.http2_header_table_size(*value),
+        Http2Setting::EnablePush => client.http2_enable_push(*value != 0),
+        Http2Setting::MaxConcurrentStreams => client.http2_max_concurrent_streams(*value),
+        Http2Setting::InitialWindowSize => client.http2_initial_stream_window_size(*value),
+        Http2Setting::MaxFrameSize => client.http2_max_frame_size(*value),
+        Http2Setting::MaxHeaderListSize => client.http2_max_header_list_size(*value),
+        Http2Setting::NoRfc7540Priorities => client.http2_no_rfc7540_priorities(*value != 0),
+      };
+    }
+
+    client
+  }
+}
+
+fn to_strings(values: &[&str]) -> Vec<String> {
+  values.iter().map(|value| value.to_string()).collect()
+}
@@ -1,89 +1,216 @@
 use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
-use hickory_proto::error::ProtoError;
-use hickory_proto::rr::rdata::svcb::SvcParamValue;
-use hickory_proto::rr::RData;
+use log::debug;
+use reqwest::header::HeaderMap;
+use url::Url;
 
-use tokio::net::TcpStream as TokioTcpStream;
-use hickory_client::client::{AsyncClient, ClientHandle};
-use hickory_client::proto::iocompat::AsyncIoTokioAsStd;
-use hickory_client::rr::Name;
-use hickory_client::tcp::TcpClientStream;
+use crate::alt_svc::AltSvc;
+use crate::dns::{expiry, BoxError, ImpitResolver};
+
+/// How long a failed `HTTPS` record lookup is remembered before the host is looked up again.
+const FAILED_LOOKUP_TTL: Duration = Duration::from_secs(60);
+
+/// The maximum number of the AliasMode `HTTPS` records followed for a host.
+const MAX_ALIAS_CHAIN: usize = 8;
+
+/// Where the HTTP/3 requests to an origin are sent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct H3Endpoint {
+    /// The host to connect to, `None` for the origin's host.
+    pub host: Option<String>,
+    /// The UDP port to connect to, `None` for the origin's port.
+    ///
+    /// HTTP/3 is only used if the port is the origin's, the requests to the endpoints on other ports are sent over TCP
+    /// (see [`TcpFallback`](crate::impit::TcpFallback)).
+    pub port: Option<u16>,
+    /// The addresses of the host from the `ipv4hint` and `ipv6hint` parameters of the `HTTPS` record, used if the host doesn't resolve.
+    pub ip_hints: Vec<IpAddr>,
+    /// The Encrypted Client Hello configuration from the `HTTPS` record, used for the TLS handshakes with the endpoint.
+    pub ech_config: Option<Vec<u8>>,
+}
+
+/// A cached answer to whether (and where) an origin supports HTTP/3.
+struct H3Support {
+    endpoint: Option<H3Endpoint>,
+    expires: Instant,
+}
 
 /// A struct encapsulating the components required to make HTTP/3 requests.
+///
+/// All the methods take `&self`, so the engine can be shared by concurrent requests.
 pub struct H3Engine {
-    /// The DNS client used to resolve DNS queries.
-    client: AsyncClient,
-    /// The background task that processes DNS queries.
-    bg_join_handle: tokio::task::JoinHandle<Result<(), ProtoError>>,
-    /// A map of hosts that support HTTP/3.
-    /// 
-    /// This is populated by the DNS queries and manual calls to `set_h3_support` (based on the `Alt-Svc` header).
-    /// Implicitly used as a cache for the DNS queries.
-    h3_alt_svc: HashMap<String, bool>,
+    /// The resolver used for the `HTTPS` record lookups, shared with the connections.
+    resolver: ImpitResolver,
+    /// A map of origins (`host:port`) and their HTTP/3 endpoints.
+    ///
+    /// This is populated by the DNS queries and the `Alt-Svc` headers of the responses (see `update_from_alt_svc`).
+    /// The entries from the DNS expire with the record TTL, the entries from the `Alt-Svc` headers with the advertised max-age.
+    h3_alt_svc: Mutex<HashMap<String, H3Support>>,
 }
 
 impl H3Engine {
-    pub async fn init() -> Self {
-        // todo: use the DNS server from the system config
-        let (stream, sender) =
-            TcpClientStream::<AsyncIoTokioAsStd<TokioTcpStream>>::new(([8, 8, 8, 8], 53).into());
-        let (client, bg) = AsyncClient::new(stream, sender, None).await.unwrap();
-
-        let bg_join_handle= tokio::spawn(bg);
-
-        H3Engine { 
-            client, 
-            bg_join_handle,
-            h3_alt_svc: HashMap::new(),
+    pub fn init(resolver: ImpitResolver) -> Self {
+        H3Engine {
+            resolver,
+            h3_alt_svc: Mutex::new(HashMap::new()),
         }
     }
 
-    pub async fn host_supports_h3(self: &mut Self, host: &String) -> bool {
-        if let Some(supports_h3) = self.h3_alt_svc.get(host) {
-            return supports_h3.to_owned();
+    /// Returns the HTTP/3 endpoint of the URL's origin, or `None` if the origin doesn't support HTTP/3.
+    pub async fn h3_endpoint(&self, url: &Url) -> Option<H3Endpoint> {
+        let (Some(host), Some(origin)) = (url.host_str(), origin_key(url)) else {
+            return None;
+        };
+
+        // The lock must not be held across the DNS query.
+        if let Some(endpoint) = self.cached_h3_endpoint(&origin) {
+            return endpoint;
         }
 
-        let domain_name = Name::from_utf8(host).unwrap();
-    
-        let response = self.client.query(
-            domain_name,
-            hickory_proto::rr::DNSClass::IN, 
-            hickory_proto::rr::RecordType::HTTPS
-        ).await;
-    
-        let dns_h3_support = response.is_ok_and(|response | {
-            response.answers().iter().any(|answer| {
-                if let RData::HTTPS(data) = answer.data().unwrap() {
-                    return data.svc_params().iter().any(|param| {
-                        if let SvcParamValue::Alpn(alpn_protocols) = param.1.clone() {
-                            return alpn_protocols.0.iter().any(|alpn| { 
-                                alpn == "h3" 
-                            })
-                        }
-    
-                        false
-                    });
+        // IP addresses don't have DNS records.
+        if host.trim_matches(|c| c == '[' || c == ']').parse::<IpAddr>().is_ok() {
+            return None;
+        }
+
+        let (endpoint, ttl) = match self.dns_h3_endpoint(host).await {
+            Ok(found) => found,
+            Err(err) => {
+                debug!("HTTPS record lookup for {} failed ({}), assuming no HTTP/3 support", host, err);
+                (None, FAILED_LOOKUP_TTL)
+            },
+        };
+
+        self.set_h3_endpoint(url, endpoint.clone(), ttl);
+        endpoint
+    }
+
+    /// Finds the HTTP/3 endpoint in the `HTTPS` records of the host, following the AliasMode records.
+    ///
+    /// Returns the endpoint with the lowest TTL of the followed records.
+    async fn dns_h3_endpoint(&self, host: &str) -> Result<(Option<H3Endpoint>, Duration), BoxError> {
+        let mut name = host.to_string();
+        let mut ttl = Duration::MAX;
+
+        for _ in 0..MAX_ALIAS_CHAIN {
+            let answer = self.resolver.lookup_https(&name).await?;
+            ttl = ttl.min(answer.ttl);
+
+            // The ServiceMode records are ignored if there is an AliasMode record (RFC 9460, section 2.4.2).
+            if let Some(alias) = answer.records.iter().find(|record| record.is_alias()) {
+                // The alias to `.` means the service isn't available.
+                if alias.target.is_empty() {
+                    return Ok((None, ttl));
+                }
+
+                name = alias.target.clone();
+                continue;
+            }
+
+            let record = answer.records.iter()
+                .filter(|record| record.alpn.iter().any(|alpn| alpn == "h3"))
+                .min_by_key(|record| record.priority);
+
+            let endpoint = record.map(|record| {
+                // The empty target is the owner name of the record.
+                let target = match record.target.is_empty() {
+                    true => &name,
+                    false => &record.target,
+                };
+
+                H3Endpoint {
+                    host: (!target.eq_ignore_ascii_case(host)).then(|| target.to_owned()),
+                    port: record.port,
+                    ip_hints: record.ipv4_hints.iter().map(|address| IpAddr::V4(*address))
+                        .chain(record.ipv6_hints.iter().map(|address| IpAddr::V6(*address)))
+                        .collect(),
+                    ech_config: record.ech_config.clone(),
                 }
-                false
-            })
-        });
+            });
+
+            return Ok((endpoint, ttl));
+        }
+
+        debug!("Too many AliasMode HTTPS records for {}, assuming no HTTP/3 support", host);
+        Ok((None, ttl))
+    }
 
-        self.set_h3_support(host, dns_h3_support);
-        dns_h3_support
+    fn cached_h3_endpoint(&self, origin: &String) -> Option<Option<H3Endpoint>> {
+        self.h3_alt_svc.lock().unwrap()
+            .get(origin)
+            .filter(|support| support.expires > Instant::now())
+            .map(|support| support.endpoint.clone())
     }
 
-    pub fn set_h3_support(self: &mut Self, host: &String, supports_h3: bool) {
-        if self.h3_alt_svc.contains_key(host) {
+    /// Caches the HTTP/3 endpoint of the URL's origin (`None` if HTTP/3 isn't supported) for `ttl` (at most a week),
+    /// and routes the HTTP/3 connections to the origin to the endpoint's host.
+    ///
+    /// Positive answers replace the cached ones, negative answers don't replace a fresh positive answer.
+    pub fn set_h3_endpoint(&self, url: &Url, endpoint: Option<H3Endpoint>, ttl: Duration) {
+        let (Some(origin), Some(host), Some(port)) = (origin_key(url), url.host_str(), url.port_or_known_default()) else {
+            return;
+        };
+
+        let mut h3_alt_svc = self.h3_alt_svc.lock().unwrap();
+        let now = Instant::now();
+
+        let fresh_positive = h3_alt_svc.get(&origin).is_some_and(|support| support.endpoint.is_some() && support.expires > now);
+        if endpoint.is_none() && fresh_positive {
             return;
         }
 
-        self.h3_alt_svc.insert(host.to_owned(), supports_h3);
+        match &endpoint {
+            Some(endpoint) => self.resolver.set_h3_alternative(host, port, endpoint.host.as_deref(), &endpoint.ip_hints),
+            None => self.resolver.remove_h3_alternative(host, port),
+        }
+
+        h3_alt_svc.insert(origin, H3Support { endpoint, expires: expiry(now, ttl) });
+    }
+
+    /// Updates the HTTP/3 endpoint of the URL's origin from the `Alt-Svc` headers of its response.
+    ///
+    /// The first `h3` alternative on the origin's port is used, as the HTTP/3 connections can't go to other ports (see [`H3Endpoint::port`]).
+    /// Without one, the first `h3` alternative is remembered and the requests use TCP. The other protocols (e.g. the `h3-29` draft) are ignored.
+    pub fn update_from_alt_svc(&self, url: &Url, headers: &HeaderMap) {
+        let (Some(origin), Some(alt_svc)) = (origin_key(url), AltSvc::from_headers(headers)) else {
+            return;
+        };
+
+        let alternative = alt_svc.alternatives("h3")
+            .find(|alternative| Some(alternative.port) == url.port_or_known_default())
+            .or_else(|| alt_svc.alternatives("h3").next());
+
+        match alternative {
+            Some(alternative) => {
+                debug!("{} supports HTTP/3 (alt-svc header, {:?}), adding to Alt-Svc cache", origin, alternative);
+
+                let endpoint = H3Endpoint {
+                    host: alternative.host.clone(),
+                    port: Some(alternative.port),
+                    ..H3Endpoint::default()
+                };
+
+                self.set_h3_endpoint(url, Some(endpoint), alternative.max_age);
+            },
+            // `clear` or no `h3` alternative, the host is looked up in the DNS again on the next request.
+            None => {
+                self.h3_alt_svc.lock().unwrap().remove(&origin);
+
+                if let (Some(host), Some(port)) = (url.host_str(), url.port_or_known_default()) {
+                    self.resolver.remove_h3_alternative(host, port);
+                }
+            },
+        }
     }
 }
 
-impl Drop for H3Engine {
-    fn drop(&mut self) {
-        self.bg_join_handle.abort();
+/// The cache key of the URL's origin. Only the `https` origins can have HTTP/3 alternatives.
+fn origin_key(url: &Url) -> Option<String> {
+    if url.scheme() != "https" {
+        return None;
     }
-}
\ No newline at end of file
+
+    Some(format!("{}:{}", url.host_str()?, url.port_or_known_default()?))
+}
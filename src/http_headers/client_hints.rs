@@ -0,0 +1,52 @@
+//! Keeps track of the client hints requested by the servers with the `Accept-CH` header.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use reqwest::header::HeaderMap;
+use url::Url;
+
+/// Per-origin store of the client hints requested with `Accept-CH`, shared by all the clones of an [`Impit`](crate::impit::Impit) instance.
+///
+/// Like in the browsers, the hints are only stored for the secure (`https`) origins.
+/// Every `Accept-CH` header replaces the hints stored for the origin, an empty header clears them.
+#[derive(Debug, Default)]
+pub struct ClientHintsStore {
+    origins: Mutex<HashMap<String, Vec<String>>>,
+}
+
+impl ClientHintsStore {
+    /// Returns the (lowercase) names of the client hints requested by the origin of the URL.
+    pub fn get(&self, url: &Url) -> Vec<String> {
+        self.origins.lock().unwrap()
+            .get(&url.origin().ascii_serialization())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Stores the hints requested by the `Accept-CH` header of the response.
+    pub fn update(&self, url: &Url, headers: &HeaderMap) {
+        if url.scheme() != "https" || !headers.contains_key("accept-ch") {
+            return;
+        }
+
+        let hints = hint_names(headers, "accept-ch");
+        let origin = url.origin().ascii_serialization();
+        let mut origins = self.origins.lock().unwrap();
+
+        match hints.is_empty() {
+            true => origins.remove(&origin),
+            false => origins.insert(origin, hints),
+        };
+    }
+}
+
+/// Returns the (lowercase) hint names listed in the `Accept-CH` or `Critical-CH` headers of the response.
+pub fn hint_names(headers: &HeaderMap, header: &str) -> Vec<String> {
+    headers.get_all(header).iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|name| name.trim().to_ascii_lowercase())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
@@ -0,0 +1,122 @@
+//! Computes the headers describing where the request comes from - `Sec-Fetch-Site`, `Referer` and `Origin`.
+
+use reqwest::Method;
+use url::{Host, Url};
+
+use crate::emulation::BrowserFamily;
+use crate::request::{HeaderPosition, RequestContext};
+
+/// Returns the `Sec-Fetch-Site` value of a request from `initiator` to `target`.
+///
+/// Requests without an initiator (e.g. URLs typed in the address bar) are `none`.
+/// The sites are compared by scheme and registrable domain (eTLD+1, using the public suffix list).
+/// For redirected requests, the least trusted relation of all the URLs in the redirect chain is used.
+pub fn sec_fetch_site(initiator: Option<&Url>, target: &Url, redirects: &[Url]) -> &'static str {
+    let Some(initiator) = initiator else {
+        return "none";
+    };
+
+    let urls = || redirects.iter().chain(std::iter::once(target));
+
+    if urls().all(|url| initiator.origin() == url.origin()) {
+        return "same-origin";
+    }
+
+    if urls().all(|url| same_site(initiator, url)) {
+        return "same-site";
+    }
+
+    "cross-site"
+}
+
+fn same_site(a: &Url, b: &Url) -> bool {
+    a.scheme() == b.scheme() && site(a).is_some() && site(a) == site(b)
+}
+
+/// Returns the registrable domain of the URL, or the whole host for IP addresses and hosts without a public suffix.
+fn site(url: &Url) -> Option<String> {
+    match url.host()? {
+        Host::Domain(domain) => {
+            let domain = domain.to_ascii_lowercase();
+
+            match psl::domain_str(&domain) {
+                Some(site) => Some(site.to_string()),
+                None => Some(domain),
+            }
+        },
+        host => Some(host.to_string()),
+    }
+}
+
+/// Returns the `Referer` value per the `strict-origin-when-cross-origin` policy, the default policy of all the supported browsers.
+///
+/// Same-origin requests get the full URL (without the fragment and credentials), cross-origin requests only the origin,
+/// and no referrer is sent when going from HTTPS to HTTP.
+pub fn referer(referrer: &Url, target: &Url) -> Option<String> {
+    if referrer.scheme() == "https" && target.scheme() != "https" {
+        return None;
+    }
+
+    if referrer.origin() == target.origin() {
+        let mut referrer = referrer.clone();
+        referrer.set_fragment(None);
+        let _ = referrer.set_username("");
+        let _ = referrer.set_password(None);
+
+        return Some(referrer.to_string());
+    }
+
+    Some(format!("{}/", referrer.origin().ascii_serialization()))
+}
+
+/// Returns the `Origin` value, if the browser would send one.
+///
+/// Browsers send the `Origin` header with all the requests except `GET` and `HEAD`, and with the cross-origin CORS requests.
+/// After a redirect from a cross-origin URL to another origin, the origin is "tainted" and sent as `null`.
+pub fn origin(referrer: &Url, target: &Url, redirects: &[Url], method: &Method, context: RequestContext) -> Option<String> {
+    let cors = is_cors(context);
+    let safe_method = method == Method::GET || method == Method::HEAD;
+    let cross_origin = redirects.iter().chain(std::iter::once(target)).any(|url| referrer.origin() != url.origin());
+
+    if safe_method && !(cors && cross_origin) {
+        return None;
+    }
+
+    let urls: Vec<&Url> = redirects.iter().chain(std::iter::once(target)).collect();
+    let tainted = urls.windows(2)
+        .any(|hop| hop[0].origin() != hop[1].origin() && referrer.origin() != hop[0].origin());
+
+    match tainted {
+        true => Some(String::from("null")),
+        false => Some(referrer.origin().ascii_serialization()),
+    }
+}
+
+/// Checks whether the browser makes the requests of the context in the `cors` mode.
+pub fn is_cors(context: RequestContext) -> bool {
+    matches!(context, RequestContext::Fetch | RequestContext::Font)
+}
+
+/// Where the browser puts the `Origin` header, as a list of positions to try.
+pub fn origin_positions(family: BrowserFamily) -> Vec<HeaderPosition> {
+    match family {
+        BrowserFamily::Chrome => vec![
+            HeaderPosition::After(String::from("sec-ch-ua-platform")),
+            HeaderPosition::Before(String::from("user-agent")),
+        ],
+        BrowserFamily::Firefox => vec![HeaderPosition::After(String::from("accept-encoding"))],
+        BrowserFamily::Safari => vec![HeaderPosition::Before(String::from("user-agent"))],
+    }
+}
+
+/// Where the browser puts the `Referer` header, as a list of positions to try.
+pub fn referer_positions(family: BrowserFamily) -> Vec<HeaderPosition> {
+    match family {
+        BrowserFamily::Chrome | BrowserFamily::Safari => vec![HeaderPosition::Before(String::from("accept-encoding"))],
+        BrowserFamily::Firefox => vec![
+            HeaderPosition::After(String::from("connection")),
+            HeaderPosition::After(String::from("origin")),
+            HeaderPosition::After(String::from("accept-encoding")),
+        ],
+    }
+}
@@ -1,71 +1,253 @@
-use std::{collections::HashMap, str::FromStr};
-use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
-use crate::emulation::Browser;
+use std::str::FromStr;
+use reqwest::header::{HeaderCaseMap, HeaderMap, HeaderName, HeaderValue};
+use reqwest::Method;
+use url::Url;
+use crate::emulation::BrowserProfile;
+use crate::request::{CustomHeader, HeaderPosition, RequestContext};
 
-mod statics;
+pub(crate) mod client_hints;
+pub(crate) mod fetch_metadata;
+pub(crate) mod statics;
 
 pub struct HttpHeaders {
     context: HttpHeadersBuilder,
+    /// The merged headers, in the order they're sent. Computed once, in [`HttpHeaders::new`].
+    headers: Vec<(String, String)>,
 }
 
 impl HttpHeaders {
     pub fn new(options: &HttpHeadersBuilder) -> HttpHeaders {
-        HttpHeaders {
+        let mut headers = HttpHeaders {
             context: options.clone(),
-        }
+            headers: vec![],
+        };
+
+        headers.headers = headers.ordered_headers();
+        headers
     }
 
     pub fn get_builder() -> HttpHeadersBuilder {
         HttpHeadersBuilder::default()
     }
-}
 
-impl Into<HeaderMap> for HttpHeaders {
-    fn into(self) -> HeaderMap {
-        let mut headers = HeaderMap::new();
-
-        let header_values = match self.context.browser {
-            Some(Browser::Chrome) => statics::CHROME_HEADERS,
-            Some(Browser::Firefox) => statics::FIREFOX_HEADERS,
+    /// Merges the default browser headers with the custom headers, returning the headers in the order they should be sent.
+    fn ordered_headers(&self) -> Vec<(String, String)> {
+        let default_headers = match &self.context.browser {
+            Some(profile) if self.context.http1 && !profile.http1_headers.is_empty() => profile.http1_headers.as_slice(),
+            Some(profile) => profile.headers.as_slice(),
             None => &[]
         };
 
-        let pseudo_headers_order: &[&str] = match self.context.browser {
-            Some(Browser::Chrome) => statics::CHROME_PSEUDOHEADERS_ORDER.as_ref(),
-            Some(Browser::Firefox) => statics::FIREFOX_PSEUDOHEADERS_ORDER.as_ref(),
-            None => &[]
+        let context_headers = self.context.browser.as_ref()
+            .and_then(|profile| profile.contexts.get(&self.context.request_context));
+
+        let is_removed = |name: &str| {
+            self.context.removed_headers.iter().any(|removed| removed.eq_ignore_ascii_case(name))
+                || context_headers.is_some_and(|context| context.removed_headers.iter().any(|removed| removed.eq_ignore_ascii_case(name)))
+        };
+
+        let context_value = |name: &str| {
+            context_headers.and_then(|context| {
+                context.headers.iter()
+                    .find(|(context_name, _)| context_name.eq_ignore_ascii_case(name))
+                    .map(|(_, value)| value.to_owned())
+            })
         };
 
-        if pseudo_headers_order.len() != 0 {
-            std::env::set_var("RETCH_H2_PSEUDOHEADERS_ORDER", pseudo_headers_order.join(","));
+        // With a known initiator, `sec-fetch-site` is computed from the URLs instead of taken from the request context.
+        // Without one, the context value is sent - `none` for navigations, `cross-site` for the other requests made by a page.
+        let sec_fetch_site = match (self.initiator(), &self.context.url) {
+            (Some(initiator), Some(url)) => Some(fetch_metadata::sec_fetch_site(Some(initiator), url, &self.context.redirects).to_string()),
+            _ => None,
+        };
+
+        let mut headers: Vec<(String, String)> = vec![];
+
+        for (name, value) in default_headers {
+            if is_removed(name) {
+                continue;
+            }
+
+            let value = match &sec_fetch_site {
+                _ if name.eq_ignore_ascii_case("host") => self.context.host.to_owned(),
+                Some(site) if name.eq_ignore_ascii_case("sec-fetch-site") => site.to_owned(),
+                _ => context_value(name).unwrap_or_else(|| value.to_owned()),
+            };
+
+            headers.push((name.to_owned(), value));
+        }
+
+        self.add_client_hints(&mut headers, &is_removed);
+        self.add_fetch_metadata(&mut headers, &is_removed);
+
+        let mut merged: Vec<(String, String)> = vec![];
+
+        for (name, value) in &headers {
+            let replacements: Vec<&CustomHeader> = self.context.custom_headers.iter()
+                .filter(|header| header.position == HeaderPosition::Default && header.name.eq_ignore_ascii_case(name))
+                .collect();
+
+            if replacements.is_empty() {
+                merged.push((name.to_owned(), value.to_owned()));
+            } else {
+                // The replacements keep the letter case of the browser header.
+                merged.extend(replacements.iter().map(|header| (name.to_owned(), header.value.to_owned())));
+            }
         }
 
-        let mut used_custom_headers: Vec<String> = vec![];
+        let mut headers = merged;
+        let mut start: Vec<(String, String)> = vec![];
 
-        // TODO: don't use HTTP2 headers for HTTP1.1
-        for (name, impersonated_value) in header_values {
-            let value: &str = match self.context.custom_headers.get(*name)  { 
-                Some(custom_value) => {
-                    used_custom_headers.push(name.to_string());
-                    custom_value.as_str()
+        let positioned: Vec<&CustomHeader> = self.context.custom_headers.iter()
+            .filter(|header| {
+                header.position != HeaderPosition::Default
+                    || !headers.iter().any(|(name, _)| name.eq_ignore_ascii_case(&header.name))
+            })
+            .collect();
+
+        for header in positioned {
+            let entry = (header.name.to_owned(), header.value.to_owned());
+
+            match &header.position {
+                HeaderPosition::Start => start.push(entry),
+                position => {
+                    let index = insertion_index(&headers, position).unwrap_or(headers.len());
+                    headers.insert(index, entry);
                 },
-                None => impersonated_value,
+            }
+        }
+
+        start.extend(headers);
+        start
+    }
+
+    /// Adds the high-entropy client hints requested by the server, right after the default client hints of the browser.
+    ///
+    /// The browsers only send the client hints over secure connections.
+    fn add_client_hints(&self, headers: &mut Vec<(String, String)>, is_removed: &dyn Fn(&str) -> bool) {
+        let Some(profile) = &self.context.browser else {
+            return;
+        };
+
+        if !self.context.https {
+            return;
+        }
+
+        let mut index = headers.iter()
+            .rposition(|(name, _)| name.to_ascii_lowercase().starts_with("sec-ch-"))
+            .map(|index| index + 1)
+            .unwrap_or(headers.len());
+
+        for (name, value) in &profile.client_hints {
+            let requested = self.context.client_hints.iter().any(|hint| hint.eq_ignore_ascii_case(name));
+
+            if !requested || is_removed(name) || headers.iter().any(|(header_name, _)| header_name.eq_ignore_ascii_case(name)) {
+                continue;
+            }
+
+            headers.insert(index, (name.to_owned(), value.to_owned()));
+            index += 1;
+        }
+    }
+
+    /// Returns the URL of the page the request is made from.
+    ///
+    /// Without a referrer, the CORS requests (`fetch()` calls and fonts) are treated as made by a page of the first requested origin,
+    /// as a cross-origin CORS request would have to send the `Origin` of the unknown page.
+    fn initiator(&self) -> Option<&Url> {
+        match &self.context.referrer {
+            Some(referrer) => Some(referrer),
+            None if fetch_metadata::is_cors(self.context.request_context) => self.context.redirects.first().or(self.context.url.as_ref()),
+            None => None,
+        }
+    }
+
+    /// Adds the `Origin` and `Referer` headers the browser would send from the initiating page, in the browser's positions.
+    fn add_fetch_metadata(&self, headers: &mut Vec<(String, String)>, is_removed: &dyn Fn(&str) -> bool) {
+        let (Some(profile), Some(initiator), Some(url)) = (&self.context.browser, self.initiator(), &self.context.url) else {
+            return;
+        };
+
+        let family = profile.tls.family;
+        // Follow the letter case of the browser's header set, e.g. the title-cased HTTP/1.1 headers.
+        let title_case = headers.iter().any(|(name, _)| name == "User-Agent");
+
+        let origin = fetch_metadata::origin(initiator, url, &self.context.redirects, &self.context.method, self.context.request_context);
+        // The URL of the initiating page is only known with a referrer.
+        let referer = self.context.referrer.as_ref().and_then(|referrer| fetch_metadata::referer(referrer, url));
+
+        let computed = [
+            ("Origin", origin, fetch_metadata::origin_positions(family)),
+            ("Referer", referer, fetch_metadata::referer_positions(family)),
+        ];
+
+        for (name, value, positions) in computed {
+            let Some(value) = value else {
+                continue;
             };
 
-            headers.append(
-                HeaderName::from_str(name).unwrap(), 
-                HeaderValue::from_str(value).unwrap()
-            );
+            if is_removed(name) || headers.iter().any(|(header_name, _)| header_name.eq_ignore_ascii_case(name)) {
+                continue;
+            }
+
+            let name = match title_case {
+                true => name.to_string(),
+                false => name.to_ascii_lowercase(),
+            };
+
+            let index = positions.iter()
+                .find_map(|position| insertion_index(headers, position))
+                .unwrap_or(headers.len());
+
+            headers.insert(index, (name, value));
+        }
+    }
+
+    /// Returns the original letter case of the header names, used for writing the HTTP/1.1 headers.
+    pub fn case_map(&self) -> HeaderCaseMap {
+        let mut case_map = HeaderCaseMap::default();
+
+        for (name, _) in &self.headers {
+            case_map.append(HeaderName::from_str(name).unwrap(), name.to_owned());
         }
 
-        self.context.custom_headers.iter().for_each(|(name, value)| {
-            if !used_custom_headers.contains(name) {
-                headers.append(
-                    HeaderName::from_str(name).unwrap(), 
-                    HeaderValue::from_str(value).unwrap()
-                );
+        case_map
+    }
+}
+
+/// Returns the index a header with the given position should be inserted at, or `None` if the anchor header is missing.
+///
+/// Only resolves the [`HeaderPosition::Before`] and [`HeaderPosition::After`] positions, the `Start` headers are handled by the caller.
+fn insertion_index(headers: &[(String, String)], position: &HeaderPosition) -> Option<usize> {
+    let anchor = |name: &String| headers.iter().position(|(header_name, _)| header_name.eq_ignore_ascii_case(name));
+
+    match position {
+        HeaderPosition::Before(name) => anchor(name),
+        HeaderPosition::After(name) => anchor(name).map(|index| {
+            // Keep all the values of a repeated anchor header together.
+            let mut index = index + 1;
+            while index < headers.len() && headers[index].0.eq_ignore_ascii_case(name) {
+                index += 1;
             }
-        });
+            index
+        }),
+        HeaderPosition::Default | HeaderPosition::Start | HeaderPosition::End => None,
+    }
+}
+
+/// Note that `HeaderMap` keeps all the values of a header name together, at the position of the first value.
+/// A repeated header is sent as adjacent lines (HTTP/1.1) or fields (HTTP/2), even if its values weren't adjacent in the merged order,
+/// e.g. `a: 1, b: 2, a: 3` is sent as `a: 1, a: 3, b: 2`.
+impl Into<HeaderMap> for HttpHeaders {
+    fn into(self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+
+        for (name, value) in self.headers {
+            headers.append(
+                HeaderName::from_str(&name).unwrap(), 
+                HeaderValue::from_str(&value).unwrap()
+            );
+        }
 
         headers
     }
@@ -74,9 +256,17 @@ impl Into<HeaderMap> for HttpHeaders {
 #[derive(Default, Clone)]
 pub struct HttpHeadersBuilder {
     host: String,
-    browser: Option<Browser>,
+    url: Option<Url>,
+    referrer: Option<Url>,
+    redirects: Vec<Url>,
+    method: Method,
+    browser: Option<BrowserProfile>,
     https: bool,
-    custom_headers: HashMap<String, String>,
+    http1: bool,
+    request_context: RequestContext,
+    client_hints: Vec<String>,
+    custom_headers: Vec<CustomHeader>,
+    removed_headers: Vec<String>,
 }
 
 impl HttpHeadersBuilder {
@@ -86,7 +276,30 @@ impl HttpHeadersBuilder {
         self
     }
 
-    pub fn with_browser (&mut self, browser: &Option<Browser>) -> &mut Self {
+    /// Sets the request URL, used for computing the `Sec-Fetch-Site`, `Referer` and `Origin` headers.
+    pub fn with_url (&mut self, url: &Url) -> &mut Self {
+        self.url = Some(url.to_owned());
+        self
+    }
+
+    /// Sets the URL of the page that initiated the request.
+    pub fn with_referrer (&mut self, referrer: &Option<Url>) -> &mut Self {
+        self.referrer = referrer.to_owned();
+        self
+    }
+
+    /// Sets the URLs the request was redirected from, in the order they were visited.
+    pub fn with_redirects (&mut self, redirects: &[Url]) -> &mut Self {
+        self.redirects = redirects.to_vec();
+        self
+    }
+
+    pub fn with_method (&mut self, method: &Method) -> &mut Self {
+        self.method = method.to_owned();
+        self
+    }
+
+    pub fn with_browser (&mut self, browser: &Option<BrowserProfile>) -> &mut Self {
         self.browser = browser.to_owned();
         self
     }
@@ -96,11 +309,33 @@ impl HttpHeadersBuilder {
         self
     }
 
-    pub fn with_custom_headers (&mut self, custom_headers: &HashMap<String, String>) -> &mut Self {
+    /// Uses the HTTP/1.1 header set of the browser.
+    pub fn with_http1 (&mut self, http1: bool) -> &mut Self {
+        self.http1 = http1;
+        self
+    }
+
+    pub fn with_request_context (&mut self, request_context: RequestContext) -> &mut Self {
+        self.request_context = request_context;
+        self
+    }
+
+    /// Sets the names of the client hints the server requested with `Accept-CH`.
+    pub fn with_client_hints (&mut self, client_hints: &[String]) -> &mut Self {
+        self.client_hints = client_hints.to_vec();
+        self
+    }
+
+    pub fn with_custom_headers (&mut self, custom_headers: &Vec<CustomHeader>) -> &mut Self {
         self.custom_headers = custom_headers.to_owned();
         self
     }
 
+    pub fn with_removed_headers (&mut self, removed_headers: &Vec<String>) -> &mut Self {
+        self.removed_headers = removed_headers.to_owned();
+        self
+    }
+
     pub fn build(&self) -> HttpHeaders {
         HttpHeaders::new(self)
     }
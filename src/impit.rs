@@ -1,14 +1,20 @@
-use std::{str::FromStr, time::Duration};
+use std::{collections::HashMap, net::IpAddr, str::FromStr, sync::{Arc, Mutex}, time::{Duration, Instant}};
 use log::debug;
-use reqwest::{Method, Response, Version};
+use reqwest::{Method, Response, StatusCode, Version};
+use tokio::sync::OnceCell;
 use url::Url;
 
-use crate::{http3::H3Engine, http_headers::HttpHeaders, tls, request::RequestOptions, emulation::Browser};
+use crate::{http3::{H3Endpoint, H3Engine}, http_headers::HttpHeaders, tls, request::{IntoUrl, RequestBuilder, RequestContext, RequestOptions}, emulation::{Browser, BrowserProfile, ProfileError, TlsFingerprint}};
+use crate::dns::{DnsConfig, HickoryResolver, ImpitResolver, Resolver};
+use crate::http_headers::client_hints::{self, ClientHintsStore};
+use crate::redirect::{RedirectAction, RedirectAttempt, RedirectHop, Redirects};
 
 /// Error types that can be returned by the [`Impit`] struct.
 /// 
 /// The `ErrorType` enum is used to represent the different types of errors that can occur when making requests.
-/// The `RequestError` variant is used to wrap the `reqwest::Error` type.
+/// The failed `reqwest` requests are classified into the more specific variants (e.g. [`ErrorType::Timeout`] or [`ErrorType::ConnectError`]),
+/// so the caller can decide whether the request is worth retrying. 
+/// The `RequestError` variant wraps the `reqwest::Error` errors that don't fit any of them.
 #[derive(Debug)]
 pub enum ErrorType {
   /// The URL couldn't be parsed.
@@ -19,19 +25,224 @@ pub enum ErrorType {
   UrlProtocolError,
   /// The request was made with `http3_prior_knowledge`, but HTTP/3 usage wasn't enabled.
   Http3Disabled,
+  /// The proxy URL passed to [`ImpitBuilder::with_proxy`] couldn't be parsed.
+  ProxyUrlError(reqwest::Error),
+  /// The browser profile passed to [`ImpitBuilder::with_browser_profile`] is invalid, see [`BrowserProfile::validate`].
+  InvalidProfile(ProfileError),
+  /// The TLS configuration couldn't be built, e.g. because a custom profile contains a cipher suite the emulator doesn't support.
+  TlsSetupError(rustls::Error),
+  /// The underlying `reqwest` client couldn't be built.
+  ClientBuildError(reqwest::Error),
+  /// The value passed to [`RequestBuilder::json`] couldn't be serialized.
+  JsonSerializationError(serde_json::Error),
+  /// The header passed to [`RequestBuilder::header`], [`RequestBuilder::header_at`] or [`RequestOptions::headers`] isn't a valid HTTP header.
+  InvalidHeader {
+    name: String,
+    value: String,
+  },
+  /// The request (or connecting to the server) didn't finish in time.
+  Timeout(reqwest::Error),
+  /// The connection to the server couldn't be established (e.g. DNS failure or a refused connection).
+  ConnectError(reqwest::Error),
+  /// The TLS handshake failed.
+  TlsHandshakeError {
+    /// The alert sent by the server, if the handshake was aborted by the server.
+    alert: Option<rustls::AlertDescription>,
+    /// The `rustls` error that caused the failure.
+    reason: rustls::Error,
+    source: reqwest::Error,
+  },
+  /// The connection to the proxy server failed, or the proxy refused to open the tunnel.
+  ProxyError(reqwest::Error),
+  /// The [`RedirectBehavior::Custom`] callback rejected a redirect.
+  RedirectRejected(Box<dyn std::error::Error + Send + Sync>),
+  /// The redirect limit set with [`ImpitBuilder::with_redirect`] was exceeded.
+  TooManyRedirects {
+    /// The maximum number of redirects.
+    limit: usize,
+    /// The redirect target that wasn't followed.
+    url: Url,
+  },
+  /// The request sent over HTTP/3 (QUIC) failed.
+  Http3Failure(reqwest::Error),
+  /// The response body couldn't be decoded (e.g. invalid compression).
+  DecodeError(reqwest::Error),
   /// `reqwest::Error` variant. See the nested error for more details.
   RequestError(reqwest::Error),
 }
 
+impl ErrorType {
+  /// Classifies the error of a failed request.
+  /// 
+  /// `h3` tells whether the request was sent over HTTP/3, `proxy` whether the request was sent through a proxy.
+  fn from_request_error(err: reqwest::Error, h3: bool, proxy: bool) -> Self {
+    if err.is_timeout() {
+      return ErrorType::Timeout(err);
+    }
+
+    if let Some(reason) = tls_error(&err) {
+      let alert = match &reason {
+        rustls::Error::AlertReceived(alert) => Some(*alert),
+        _ => None,
+      };
+
+      return ErrorType::TlsHandshakeError { alert, reason, source: err };
+    }
+
+    if err.is_decode() {
+      return ErrorType::DecodeError(err);
+    }
+
+    if h3 {
+      return ErrorType::Http3Failure(err);
+    }
+
+    if err.is_connect() {
+      return match proxy {
+        true => ErrorType::ProxyError(err),
+        false => ErrorType::ConnectError(err),
+      };
+    }
+
+    ErrorType::RequestError(err)
+  }
+}
+
+impl std::fmt::Display for ErrorType {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ErrorType::UrlParsingError => write!(f, "the URL couldn't be parsed"),
+      ErrorType::UrlMissingHostnameError => write!(f, "the URL is missing the hostname"),
+      ErrorType::UrlProtocolError => write!(f, "the URL uses an unsupported protocol"),
+      ErrorType::Http3Disabled => write!(f, "HTTP/3 prior knowledge was requested, but HTTP/3 is disabled"),
+      ErrorType::ProxyUrlError(err) => write!(f, "invalid proxy URL: {}", err),
+      ErrorType::InvalidProfile(err) => write!(f, "invalid browser profile: {}", err),
+      ErrorType::TlsSetupError(err) => write!(f, "invalid TLS configuration: {}", err),
+      ErrorType::ClientBuildError(err) => write!(f, "the HTTP client couldn't be built: {}", err),
+      ErrorType::JsonSerializationError(err) => write!(f, "the JSON body couldn't be serialized: {}", err),
+      ErrorType::InvalidHeader { name, value } => write!(f, "invalid header {:?}: {:?}", name, value),
+      ErrorType::Timeout(err) => write!(f, "the request timed out: {}", err),
+      ErrorType::ConnectError(err) => write!(f, "connection failed: {}", err),
+      ErrorType::TlsHandshakeError { alert: Some(alert), .. } => write!(f, "TLS handshake failed, the server sent the {:?} alert", alert),
+      ErrorType::TlsHandshakeError { reason, .. } => write!(f, "TLS handshake failed: {}", reason),
+      ErrorType::ProxyError(err) => write!(f, "proxy connection failed: {}", err),
+      ErrorType::RedirectRejected(err) => write!(f, "the redirect was rejected: {}", err),
+      ErrorType::TooManyRedirects { limit, url } => write!(f, "too many redirects (limit {}), the next one pointed to {}", limit, url),
+      ErrorType::Http3Failure(err) => write!(f, "HTTP/3 request failed: {}", err),
+      ErrorType::DecodeError(err) => write!(f, "the response couldn't be decoded: {}", err),
+      ErrorType::RequestError(err) => write!(f, "request failed: {}", err),
+    }
+  }
+}
+
+impl std::error::Error for ErrorType {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      ErrorType::InvalidProfile(err) => Some(err),
+      ErrorType::TlsSetupError(err) => Some(err),
+      ErrorType::JsonSerializationError(err) => Some(err),
+      ErrorType::TlsHandshakeError { source, .. } => Some(source),
+      ErrorType::RedirectRejected(err) => Some(err.as_ref()),
+      ErrorType::ProxyUrlError(err)
+      | ErrorType::ClientBuildError(err)
+      | ErrorType::Timeout(err)
+      | ErrorType::ConnectError(err)
+      | ErrorType::ProxyError(err)
+      | ErrorType::Http3Failure(err)
+      | ErrorType::DecodeError(err)
+      | ErrorType::RequestError(err) => Some(err),
+      _ => None,
+    }
+  }
+}
+
+/// Describes which client has served the response.
+/// 
+/// Every successful response returned by the [`Impit`] methods carries this marker in its extensions,
+/// so the caller can tell whether the impersonation worked or the request had to fall back to the vanilla client.
+/// 
+/// ### Example
+/// ```rust
+/// let response = impit.get("https://example.com".to_string(), None).await.unwrap();
+/// 
+/// if let Some(ServedBy::VanillaFallback) = response.extensions().get::<ServedBy>() {
+///   println!("Impersonation failed, the response was served by the vanilla client.");
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServedBy {
+  /// The response was served by the client impersonating the selected browser.
+  Impersonated,
+  /// No browser was selected, the response was served by the default `reqwest` client.
+  Vanilla,
+  /// The impersonated request failed on a TLS-related error and was retried without impersonation.
+  VanillaFallback,
+}
+
+/// Describes why a request to an origin with a known HTTP/3 endpoint was sent over TCP.
+/// 
+/// The responses to such requests carry this marker in their extensions, next to [`ServedBy`].
+/// 
+/// ### Example
+/// ```rust
+/// let response = impit.get("https://example.com".to_string(), None).await.unwrap();
+/// 
+/// if let Some(reason) = response.extensions().get::<TcpFallback>() {
+///   println!("HTTP/3 wasn't used: {:?}", reason);
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TcpFallback {
+  /// The endpoint is on another port than the origin. The connections always go to the origin's port.
+  EndpointOnOtherPort,
+  /// Another origin of the same host (on another port) has a different HTTP/3 endpoint.
+  /// The connections are routed by the host name only, so the two origins can't be told apart.
+  AmbiguousEndpoint,
+}
+
 /// Impit is the main struct used to make (impersonated) requests.
 /// 
 /// It uses `reqwest::Client` to make requests and holds info about the impersonated browser.
 /// 
 /// To create a new [`Impit`] instance, use the [`Impit::builder()`](ImpitBuilder) method.
+/// 
+/// The instance is cheap to clone - the clones share the connection pools, cookies and the HTTP/3 support cache,
+/// so one instance can be used from many concurrent tasks.
+/// 
+/// ### Example
+/// ```rust
+/// let impit = Impit::builder()
+///   .with_browser(Browser::Firefox(FirefoxVersion::V133))
+///   .build();
+/// 
+/// let tasks = (0..10).map(|_| {
+///   let impit = impit.clone();
+///   tokio::spawn(async move { impit.get("https://example.com".to_string(), None).await })
+/// });
+/// ```
+#[derive(Clone)]
 pub struct Impit {
-  pub(self) base_client: reqwest::Client,
-  pub(self) h3_client: Option<reqwest::Client>,
-  h3_engine: Option<H3Engine>,
+  inner: Arc<ImpitInner>,
+}
+
+/// The state of an [`Impit`] instance, shared by all its clones.
+struct ImpitInner {
+  base_client: reqwest::Client,
+  h3_client: Option<reqwest::Client>,
+  /// The HTTP/3 clients of the endpoints with an ECH configuration, by the configuration.
+  /// 
+  /// The TLS configuration is shared by all the connections of a client, so each ECH configuration needs its own client.
+  h3_ech_clients: Mutex<HashMap<Vec<u8>, reqwest::Client>>,
+  /// Non-impersonated client used for retrying the failed requests. 
+  /// 
+  /// Only available if a browser is selected and [`ImpitBuilder::with_fallback_to_vanilla`] is enabled.
+  vanilla_client: Option<reqwest::Client>,
+  /// Lazily initialized on the first HTTP/3-enabled request.
+  h3_engine: OnceCell<H3Engine>,
+  /// Resolves the hosts for all the clients and the HTTP/3 discovery.
+  resolver: ImpitResolver,
+  /// Client hints requested by the servers.
+  client_hints: ClientHintsStore,
   config: ImpitBuilder,
 }
 
@@ -44,7 +255,7 @@ impl Default for Impit {
 /// Customizes the behavior of the [`Impit`] struct when following redirects.
 /// 
 /// The `RedirectBehavior` enum is used to specify how the client should handle redirects.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub enum RedirectBehavior {
   /// Follow up to `usize` redirects.
   /// 
@@ -54,6 +265,33 @@ pub enum RedirectBehavior {
   /// 
   /// The client will return the response for the first request, even with the `3xx` status code.
   ManualRedirect,
+  /// Call the function for every redirect to decide whether to follow it, stop or fail the request.
+  /// 
+  /// There is no redirect limit, the function has to stop the redirect loops itself.
+  /// 
+  /// ### Example
+  /// ```rust
+  /// let impit = Impit::builder()
+  ///   .with_redirect(RedirectBehavior::Custom(Arc::new(|attempt: &RedirectAttempt| {
+  ///     match attempt.history.len() {
+  ///       0..=5 if attempt.next.host_str() == Some("example.com") => RedirectAction::Follow,
+  ///       0..=5 => RedirectAction::Stop,
+  ///       _ => RedirectAction::Error("redirect loop".into()),
+  ///     }
+  ///   })))
+  ///   .build();
+  /// ```
+  Custom(Arc<dyn Fn(&RedirectAttempt) -> RedirectAction + Send + Sync>),
+}
+
+impl std::fmt::Debug for RedirectBehavior {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      RedirectBehavior::FollowRedirect(limit) => f.debug_tuple("FollowRedirect").field(limit).finish(),
+      RedirectBehavior::ManualRedirect => write!(f, "ManualRedirect"),
+      RedirectBehavior::Custom(_) => write!(f, "Custom(..)"),
+    }
+  }
 }
 
 /// A builder struct used to create a new [`Impit`] instance.
@@ -62,8 +300,8 @@ pub enum RedirectBehavior {
 /// 
 /// ### Example
 /// ```rust
-/// let mut impit = Impit::builder()
-///   .with_browser(Browser::Firefox)
+/// let impit = Impit::builder()
+///   .with_browser(Browser::Firefox(FirefoxVersion::V133))
 ///   .with_ignore_tls_errors(true)
 ///   .with_proxy("http://localhost:8080".to_string())
 ///   .with_default_timeout(Duration::from_secs(10))
@@ -74,13 +312,16 @@ pub enum RedirectBehavior {
 /// ```
 #[derive(Debug, Clone)]
 pub struct ImpitBuilder {
-  browser: Option<Browser>,
+  browser: Option<BrowserProfile>,
   ignore_tls_errors: bool,
   vanilla_fallback: bool,
   proxy_url: String,
   request_timeout: Duration,
   max_http_version: Version,
   redirect: RedirectBehavior,
+  dns: DnsConfig,
+  resolver: Option<Arc<dyn Resolver>>,
+  host_overrides: HashMap<String, Vec<IpAddr>>,
 }
 
 impl Default for ImpitBuilder {
@@ -93,6 +334,9 @@ impl Default for ImpitBuilder {
       request_timeout: Duration::from_secs(30),
       max_http_version: Version::HTTP_2,
       redirect: RedirectBehavior::FollowRedirect(10),
+      dns: DnsConfig::System,
+      resolver: None,
+      host_overrides: HashMap::new(),
     }
   }
 }
@@ -104,7 +348,25 @@ impl ImpitBuilder {
   /// 
   /// If not used, the client will use the default `reqwest` fingerprints.
   pub fn with_browser(mut self, browser: Browser) -> Self {
-    self.browser = Some(browser);
+    self.browser = Some(browser.into());
+    self
+  }
+
+  /// Sets a custom browser profile to impersonate.
+  /// 
+  /// Use this to impersonate browsers (or browser releases) that aren't available as [`Browser`] presets.
+  /// The profile can be loaded from a JSON or TOML file with [`BrowserProfile::from_file`].
+  /// 
+  /// ### Example
+  /// ```rust
+  /// let profile = BrowserProfile::from_file("profiles/chrome-132.toml").unwrap();
+  /// 
+  /// let impit = Impit::builder()
+  ///   .with_browser_profile(profile)
+  ///   .build();
+  /// ```
+  pub fn with_browser_profile(mut self, profile: BrowserProfile) -> Self {
+    self.browser = Some(profile);
     self
   }
 
@@ -116,6 +378,9 @@ impl ImpitBuilder {
 
   /// If set to `true`, the client will retry the request without impersonation 
   /// if the impersonated browser encounters an error.
+  /// 
+  /// Only TLS-related errors (e.g. failed handshakes) cause the retry. 
+  /// The responses served by the fallback client are marked with [`ServedBy::VanillaFallback`].
   pub fn with_fallback_to_vanilla(mut self, vanilla_fallback: bool) -> Self {
     self.vanilla_fallback = vanilla_fallback;
     self
@@ -125,6 +390,9 @@ impl ImpitBuilder {
   /// 
   /// Note that this proxy will be used for all the requests 
   /// made by the built [`Impit`] instance.
+  /// 
+  /// The target hosts are resolved by the proxy, not by the configured resolver,
+  /// and HTTP/3 is disabled, as the QUIC connections can't be tunneled through the proxy.
   pub fn with_proxy(mut self, proxy_url: String) -> Self {
     self.proxy_url = proxy_url;
     self
@@ -141,15 +409,57 @@ impl ImpitBuilder {
   /// Enables HTTP/3 usage for requests.
   ///
   /// `impit` currently supports HTTP/3 negotiation via the HTTPS DNS record and the `Alt-Svc` header.
+  /// The discovered support is cached for the record TTL or the `Alt-Svc` max-age, `Alt-Svc: clear` invalidates it.
+  /// The requests are sent to the advertised endpoint - the host and port from `Alt-Svc` (see [`AltSvc`](crate::alt_svc::AltSvc)),
+  /// or the target, port and address hints of the `HTTPS` record (following the AliasMode records).
   /// To enforce HTTP/3 usage, use the `http3_prior_knowledge` option in the `RequestOptions` struct when
   /// making the request.
   ///
+  /// HTTP/3 isn't used when a proxy is set, see [`ImpitBuilder::with_proxy`].
+  ///
   /// Note that this feature is experimental and may not work as expected with all servers.
   pub fn with_http3(mut self) -> Self {
     self.max_http_version = Version::HTTP_3;
     self
   }
 
+  /// Restricts the client to HTTP/1.1.
+  ///
+  /// The requests use the HTTP/1.1 header set of the impersonated browser, with the browser's header name casing preserved on the wire.
+  /// Note that only `http/1.1` is offered in the TLS ALPN extension, which differs from the browsers.
+  /// 
+  /// Plain-text `http://` requests always use HTTP/1.1, even without this option.
+  pub fn with_http1_only(mut self) -> Self {
+    self.max_http_version = Version::HTTP_11;
+    self
+  }
+
+  /// Sets the nameservers used for the DNS lookups - resolving the hosts to connect to and the `HTTPS` record lookups discovering the HTTP/3 support.
+  /// 
+  /// By default, the nameservers from the system configuration (`/etc/resolv.conf`) are used. See [`DnsConfig`].
+  /// 
+  /// With a proxy, the proxy resolves the hosts and HTTP/3 isn't used, so no lookups are made for the requested hosts.
+  pub fn with_dns(mut self, dns: DnsConfig) -> Self {
+    self.dns = dns;
+    self
+  }
+
+  /// Sets a custom resolver, used both for the connections and the `HTTPS` record lookups. The [`DnsConfig`] is ignored then.
+  /// 
+  /// The resolver answers are cached for their TTL.
+  pub fn with_resolver(mut self, resolver: Arc<dyn Resolver>) -> Self {
+    self.resolver = Some(resolver);
+    self
+  }
+
+  /// Connects to the given addresses instead of resolving the host, like the `--resolve` option of `curl`.
+  /// 
+  /// The host is matched case-insensitively. The `Host` header and the TLS SNI still use the original host.
+  pub fn with_host_override(mut self, host: impl Into<String>, addresses: Vec<IpAddr>) -> Self {
+    self.host_overrides.insert(host.into(), addresses);
+    self
+  }
+
   /// Sets the desired redirect behavior.
   ///
   /// By default, the client will follow up to 10 redirects.
@@ -161,7 +471,24 @@ impl ImpitBuilder {
   }
   
   /// Builds the [`Impit`] instance.
+  /// 
+  /// Panics if the configuration is invalid (e.g. on an invalid proxy URL). 
+  /// Use [`ImpitBuilder::try_build`] to handle the errors.
   pub fn build(self) -> Impit {
+    self.try_build().expect("The Impit configuration should be valid.")
+  }
+
+  /// Builds the [`Impit`] instance, returning an error if the configuration is invalid.
+  /// 
+  /// ### Example
+  /// ```rust
+  /// let impit = Impit::builder()
+  ///   .with_proxy("not a proxy url".to_string())
+  ///   .try_build();
+  /// 
+  /// assert!(matches!(impit, Err(ErrorType::ProxyUrlError(_))));
+  /// ```
+  pub fn try_build(self) -> Result<Impit, ErrorType> {
     Impit::new(self)
   }
 }
@@ -171,77 +498,166 @@ impl Impit {
     ImpitBuilder::default()
   }
 
-  fn new_reqwest_client(config: &ImpitBuilder) -> Result<reqwest::Client, reqwest::Error> {
-    let mut client = reqwest::Client::builder();
+  /// Starts building a request with the given method and URL.
+  /// 
+  /// Any HTTP method can be used, including the extension methods (e.g. `PROPFIND`). 
+  /// The request is sent with [`RequestBuilder::send`].
+  /// 
+  /// ### Example
+  /// ```rust
+  /// let response = impit.request(Method::POST, "https://example.com/api")
+  ///   .header("x-api-key", "secret")
+  ///   .query(&[("page", "2")])
+  ///   .json(&serde_json::json!({ "name": "impit" }))
+  ///   .timeout(Duration::from_secs(5))
+  ///   .send()
+  ///   .await;
+  /// 
+  /// let propfind = Method::from_bytes(b"PROPFIND").unwrap();
+  /// let response = impit.request(propfind, "https://example.com/dav/").send().await;
+  /// ```
+  pub fn request(&self, method: Method, url: impl IntoUrl) -> RequestBuilder<'_> {
+    RequestBuilder::new(self, method, url)
+  }
+
+  /// Configures the TLS client of the given configuration - the browser ClientHello and the ALPN protocols.
+  fn tls_config_builder(config: &ImpitBuilder) -> tls::TlsConfigBuilder {
     let mut tls_config_builder = tls::TlsConfig::builder();
-    let mut tls_config_builder = tls_config_builder.with_browser(config.browser);
+
+    tls_config_builder
+      .with_browser(config.browser.as_ref().map(|profile| profile.tls.clone()))
+      .with_ignore_tls_errors(config.ignore_tls_errors);
 
     if config.max_http_version == Version::HTTP_3 {
-      tls_config_builder = tls_config_builder.with_http3();
+      tls_config_builder.with_http3();
+    }
+
+    if config.max_http_version == Version::HTTP_11 {
+      tls_config_builder.with_http1_only();
     }
 
-    tls_config_builder = tls_config_builder.with_ignore_tls_errors(config.ignore_tls_errors);
+    tls_config_builder
+  }
+
+  /// The configuration of the clients sending the requests over TCP. Only the HTTP/3 client uses HTTP/3.
+  fn tcp_config(config: &ImpitBuilder) -> ImpitBuilder {
+    ImpitBuilder {
+      max_http_version: config.max_http_version.min(Version::HTTP_2),
+      ..config.clone()
+    }
+  }
+
+  fn new_reqwest_client(config: &ImpitBuilder, resolver: &ImpitResolver) -> Result<reqwest::Client, ErrorType> {
+    Self::new_reqwest_client_with_tls(config, &Self::tls_config_builder(config), resolver)
+  }
+
+  fn new_reqwest_client_with_tls(config: &ImpitBuilder, tls_config_builder: &tls::TlsConfigBuilder, resolver: &ImpitResolver) -> Result<reqwest::Client, ErrorType> {
+    let mut client = reqwest::Client::builder();
+    let tls_config = tls_config_builder.build().map_err(ErrorType::TlsSetupError)?;
 
-    let tls_config = tls_config_builder.build();
+    if let Some(profile) = &config.browser {
+      client = profile.http2.apply(client);
+    }
 
     client = client
       .danger_accept_invalid_certs(config.ignore_tls_errors)
       .danger_accept_invalid_hostnames(config.ignore_tls_errors)
       .use_preconfigured_tls(tls_config)
       .cookie_store(true)
+      .dns_resolver(Arc::new(resolver.clone()))
+      .http1_preserve_header_case(true)
       .timeout(config.request_timeout);
 
     if config.max_http_version == Version::HTTP_3 {
       client = client.http3_prior_knowledge();
     }
 
+    if config.max_http_version == Version::HTTP_11 {
+      client = client.http1_only();
+    }
+
     if config.proxy_url.len() > 0 {
       client = client.proxy(
         reqwest::Proxy::all(&config.proxy_url)
-        .expect("The proxy_url option should be a valid URL.")
+        .map_err(ErrorType::ProxyUrlError)?
       );
     }
 
-    match config.redirect {
-      RedirectBehavior::FollowRedirect(max) => {
-        client = client.redirect(reqwest::redirect::Policy::limited(max));
-      },
-      RedirectBehavior::ManualRedirect => {
-        client = client.redirect(reqwest::redirect::Policy::none());
-      },
-    }
+    // The redirects are followed by `Impit::make_request`, which rebuilds the browser headers for every hop.
+    client = client
+      .redirect(reqwest::redirect::Policy::none())
+      .referer(false);
 
-    client.build()
+    client.build().map_err(ErrorType::ClientBuildError)
   }
 
   /// Creates a new [`Impit`] instance based on the options stored in the [`ImpitBuilder`] instance.
-  fn new(config: ImpitBuilder) -> Self {
-    let mut h3_client: Option<reqwest::Client> = None;
-    let mut base_client = Self::new_reqwest_client(&config).unwrap();
-
-    if config.max_http_version == Version::HTTP_3 {
-      h3_client = Some(base_client);
-      base_client = Self::new_reqwest_client(&ImpitBuilder {
-        max_http_version: Version::HTTP_2,
-        ..config.clone()
-      }).unwrap();
+  fn new(config: ImpitBuilder) -> Result<Self, ErrorType> {
+    if let Some(profile) = &config.browser {
+      profile.validate().map_err(ErrorType::InvalidProfile)?;
     }
 
-    Impit { 
-      base_client, 
-      h3_client,
-      config,
-      h3_engine: None,
-    }
+    let resolver = ImpitResolver::new(
+      config.resolver.clone().unwrap_or_else(|| Arc::new(HickoryResolver::new(&config.dns))),
+      config.host_overrides.clone(),
+    );
+
+    // The HTTP/3 client connects to the alternative services advertised by `Alt-Svc`.
+    let h3_client = match config.max_http_version == Version::HTTP_3 {
+      true => Some(Self::new_reqwest_client(&config, &resolver.for_h3())?),
+      false => None,
+    };
+
+    let base_client = Self::new_reqwest_client(&Self::tcp_config(&config), &resolver)?;
+
+    let vanilla_client = if config.vanilla_fallback && config.browser.is_some() {
+      Some(Self::new_reqwest_client(&ImpitBuilder {
+        browser: None,
+        ..Self::tcp_config(&config)
+      }, &resolver)?)
+    } else {
+      None
+    };
+
+    Ok(Impit {
+      inner: Arc::new(ImpitInner {
+        base_client,
+        h3_client,
+        h3_ech_clients: Mutex::new(HashMap::new()),
+        vanilla_client,
+        config,
+        h3_engine: OnceCell::new(),
+        resolver,
+        client_hints: ClientHintsStore::default(),
+      }),
+    })
   }
 
-  fn parse_url(&self, url: String) -> Result<Url, ErrorType> {
-    let url = Url::parse(&url);
+  /// Returns the TLS fingerprints (JA3, JA3N and JA4) of the ClientHello this instance sends over TCP.
+  /// 
+  /// The ClientHello is rendered locally (for `example.com`) with the configuration of the TCP client, no request is made.
+  /// With [`ImpitBuilder::with_http1_only`], only `http/1.1` is offered in ALPN. With [`ImpitBuilder::with_http3`], the TCP connections
+  /// still offer `h2` - the QUIC handshakes of the HTTP/3 requests offer `h3` and aren't covered.
+  /// This is useful for checking that an upgrade didn't change the impersonated fingerprint.
+  /// 
+  /// ### Example
+  /// ```rust
+  /// let impit = Impit::builder()
+  ///   .with_browser(Browser::Firefox(FirefoxVersion::V133))
+  ///   .build();
+  /// 
+  /// println!("{}", impit.tls_fingerprint()?.ja4);
+  /// ```
+  pub fn tls_fingerprint(&self) -> Result<TlsFingerprint, ErrorType> {
+    let client_hello = Self::tls_config_builder(&Self::tcp_config(&self.inner.config))
+      .client_hello()
+      .map_err(ErrorType::TlsSetupError)?;
 
-    if url.is_err() {
-      return Err(ErrorType::UrlParsingError);
-    }
-    let url = url.unwrap();
+    Ok(TlsFingerprint::from_client_hello(&client_hello))
+  }
+
+  fn parse_url(&self, url: impl IntoUrl) -> Result<Url, ErrorType> {
+    let url = url.into_url()?;
 
     if url.host_str().is_none() {
       return Err(ErrorType::UrlMissingHostnameError);
@@ -256,49 +672,226 @@ impl Impit {
     };
   }
 
-  async fn should_use_h3(self: &mut Self, host: &String) -> bool {
-    if self.config.max_http_version < Version::HTTP_3 {
+  /// Returns where to send the request over HTTP/3, or `None` if HTTP/3 shouldn't be used.
+  /// 
+  /// Fails with the reason if the origin has an HTTP/3 endpoint that can't be used.
+  async fn h3_endpoint(&self, url: &Url) -> Result<Option<H3Endpoint>, TcpFallback> {
+    if self.inner.config.max_http_version < Version::HTTP_3 {
       debug!("HTTP/3 is disabled, falling back to TCP-based requests.");
-      return false;
+      return Ok(None);
+    }
+
+    // QUIC can't be tunneled through the proxy, the requests would bypass it.
+    if !self.inner.config.proxy_url.is_empty() {
+      debug!("HTTP/3 can't be used through a proxy, falling back to TCP-based requests.");
+      return Ok(None);
+    }
+
+    let h3_engine = self.inner.h3_engine
+      .get_or_init(|| async { H3Engine::init(self.inner.resolver.clone()) })
+      .await;
+
+    let Some(endpoint) = h3_engine.h3_endpoint(url).await else {
+      return Ok(None);
+    };
+
+    // The connection port is taken from the URL, changing it would also change `:authority`.
+    if endpoint.port.is_some_and(|port| Some(port) != url.port_or_known_default()) {
+      debug!("The HTTP/3 endpoint of {} is on another port ({:?}), falling back to TCP-based requests.", url, endpoint);
+      return Err(TcpFallback::EndpointOnOtherPort);
     }
 
-    if let None = &self.h3_engine {
-      self.h3_engine = Some(H3Engine::init().await);
+    // The alternative host is picked by the resolver, which doesn't know the port.
+    if url.host_str().is_some_and(|host| self.inner.resolver.h3_alternative_is_ambiguous(host)) {
+      debug!("The HTTP/3 origins of {} have different alternatives, falling back to TCP-based requests.", url);
+      return Err(TcpFallback::AmbiguousEndpoint);
     }
 
-    self.h3_engine.as_mut().unwrap().host_supports_h3(host).await
+    Ok(Some(endpoint))
   }
 
-  async fn make_request(&mut self, method: Method, url: String, body: Option<Vec<u8>>, options: Option<RequestOptions>) -> Result<Response, ErrorType> {
-    let options = options.unwrap_or_default();
+  /// Returns the HTTP/3 client for the endpoint, creating a client with the endpoint's ECH configuration if it has one.
+  fn h3_client(&self, endpoint: &H3Endpoint) -> Result<reqwest::Client, ErrorType> {
+    let Some(ech_config) = &endpoint.ech_config else {
+      return Ok(self.inner.h3_client.clone().unwrap());
+    };
+
+    let mut h3_ech_clients = self.inner.h3_ech_clients.lock().unwrap();
+
+    if let Some(client) = h3_ech_clients.get(ech_config) {
+      return Ok(client.clone());
+    }
 
-    if options.http3_prior_knowledge && self.config.max_http_version < Version::HTTP_3 {
+    let mut tls_config_builder = Self::tls_config_builder(&self.inner.config);
+    tls_config_builder.with_ech_config(Some(ech_config.clone()));
+
+    let client = Self::new_reqwest_client_with_tls(&self.inner.config, &tls_config_builder, &self.inner.resolver.for_h3())?;
+    h3_ech_clients.insert(ech_config.clone(), client.clone());
+
+    Ok(client)
+  }
+
+  /// Sends the request, following the redirects the way the browser would.
+  /// 
+  /// Every hop gets the browser headers rebuilt for its URL (`Host`, `Sec-Fetch-Site`, `Referer`, `Origin`).
+  /// `301` and `302` responses to `POST` requests and `303` responses to all but `HEAD` requests change the method to `GET` and drop the body,
+  /// `307` and `308` responses replay the request as is. The `Authorization` header is dropped after a cross-origin redirect.
+  pub(crate) async fn make_request(&self, method: Method, url: impl IntoUrl, body: Option<Vec<u8>>, options: Option<RequestOptions>) -> Result<Response, ErrorType> {
+    let mut options = options.unwrap_or_default();
+
+    if options.http3_prior_knowledge && self.inner.config.max_http_version < Version::HTTP_3 {
       return Err(ErrorType::Http3Disabled);
     }
 
-    let parsed_url = self.parse_url(url.clone())
-      .expect("URL should be a valid URL");
+    let mut method = method;
+    let mut body = body;
+    let mut url = self.parse_url(url)?;
+
+    for header in &options.headers {
+      header.validate()?;
+    }
+
+    let mut history: Vec<RedirectHop> = vec![];
+    let mut critical_hints_retried = false;
+
+    loop {
+      let redirects: Vec<Url> = history.iter().map(|hop| hop.url.clone()).collect();
+      let sent_hints = self.inner.client_hints.get(&url);
+
+      let started = Instant::now();
+      let mut response = self.send_once(method.clone(), url.clone(), body.clone(), &options, &redirects, &sent_hints).await?;
+      let elapsed = started.elapsed();
+
+      self.inner.client_hints.update(&url, response.headers());
+
+      // Like Chrome, retry the navigation once if the server marked some of the newly requested hints as critical.
+      if !critical_hints_retried && self.missing_critical_hints(&url, &response, &sent_hints, options.context) {
+        debug!("{} requires client hints that weren't sent, retrying the request", url);
+        critical_hints_retried = true;
+        continue;
+      }
+
+      let Some(location) = redirect_location(&response, &url) else {
+        response.extensions_mut().insert(Redirects(history));
+        return Ok(response);
+      };
+
+      let status = response.status();
+
+      history.push(RedirectHop {
+        url: url.clone(),
+        status,
+        headers: response.headers().clone(),
+        elapsed,
+      });
+
+      let action = match &self.inner.config.redirect {
+        RedirectBehavior::FollowRedirect(limit) if history.len() > *limit => {
+          return Err(ErrorType::TooManyRedirects { limit: *limit, url: location });
+        },
+        RedirectBehavior::FollowRedirect(_) => RedirectAction::Follow,
+        RedirectBehavior::ManualRedirect => RedirectAction::Stop,
+        RedirectBehavior::Custom(callback) => callback(&RedirectAttempt { history: &history, next: &location }),
+      };
+
+      match action {
+        RedirectAction::Follow => {},
+        RedirectAction::Stop => {
+          // The redirect response is the final response, it's not a part of the history.
+          history.pop();
+          response.extensions_mut().insert(Redirects(history));
+          return Ok(response);
+        },
+        RedirectAction::Error(err) => return Err(ErrorType::RedirectRejected(err)),
+      }
+
+      let location = self.parse_url(location)?;
+
+      if (matches!(status, StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND) && method == Method::POST)
+        || (status == StatusCode::SEE_OTHER && method != Method::HEAD) {
+        method = Method::GET;
+        body = None;
+        options.headers.retain(|header| !REQUEST_BODY_HEADERS.iter().any(|name| header.name.eq_ignore_ascii_case(name)));
+      }
+
+      if url.origin() != location.origin() {
+        options.headers.retain(|header| !header.name.eq_ignore_ascii_case("authorization"));
+      }
+
+      debug!("Following the {} redirect from {} to {}", status, url, location);
+      url = location;
+    }
+  }
+
+  /// Tells whether the response lists a client hint in `Critical-CH` that the browser would send now, but didn't send with the request.
+  fn missing_critical_hints(&self, url: &Url, response: &Response, sent_hints: &[String], context: RequestContext) -> bool {
+    let Some(profile) = &self.inner.config.browser else {
+      return false;
+    };
+
+    if !matches!(context, RequestContext::Navigate | RequestContext::Iframe) {
+      return false;
+    }
+
+    let requested_hints = self.inner.client_hints.get(url);
+
+    client_hints::hint_names(response.headers(), "critical-ch").iter().any(|hint| {
+      requested_hints.contains(hint)
+        && !sent_hints.contains(hint)
+        && profile.client_hints.iter().any(|(name, _)| name.eq_ignore_ascii_case(hint))
+    })
+  }
+
+  /// Sends a single request, without following the redirects.
+  async fn send_once(&self, method: Method, parsed_url: Url, body: Option<Vec<u8>>, options: &RequestOptions, redirects: &[Url], client_hints: &[String]) -> Result<Response, ErrorType> {
     let host = parsed_url.host_str().unwrap().to_string();
 
-    let h3 = options.http3_prior_knowledge || self.should_use_h3(&host).await;
+    let authority = match parsed_url.port() {
+      Some(port) => format!("{}:{}", host, port),
+      None => host.clone(),
+    };
+
+    let (h3_endpoint, tcp_fallback) = match options.http3_prior_knowledge {
+      true => (Some(H3Endpoint::default()), None),
+      false => match self.h3_endpoint(&parsed_url).await {
+        Ok(endpoint) => (endpoint, None),
+        Err(reason) => (None, Some(reason)),
+      },
+    };
+
+    let h3 = h3_endpoint.is_some();
+    let http1 = !h3 && (self.inner.config.max_http_version == Version::HTTP_11 || parsed_url.scheme() == "http");
 
     let headers = HttpHeaders::get_builder()
-      .with_browser(&self.config.browser)
-      .with_host(&host)
+      .with_browser(&self.inner.config.browser)
+      .with_host(&authority)
+      .with_url(&parsed_url)
+      .with_referrer(&options.referrer)
+      .with_redirects(redirects)
+      .with_method(&method)
       .with_https(parsed_url.scheme() == "https")
+      .with_http1(http1)
+      .with_request_context(options.context)
+      .with_client_hints(client_hints)
       .with_custom_headers(&options.headers)
+      .with_removed_headers(&options.removed_headers)
       .build();
+    let case_map = headers.case_map();
 
-    let client = if h3 {
-      debug!("Using QUIC for request to {}", url);
-      self.h3_client.as_ref().unwrap()
-    } else {
-      debug!("{} doesn't seem to have HTTP3 support", url);
-      &self.base_client
+    // The HTTP/3 client reaches the endpoint's host through the resolver, the request URL stays the same.
+    let client = match &h3_endpoint {
+      Some(endpoint) => {
+        debug!("Using QUIC for request to {} ({:?})", parsed_url, endpoint);
+        self.h3_client(endpoint)?
+      },
+      None => {
+        debug!("{} doesn't seem to have HTTP3 support", parsed_url);
+        self.inner.base_client.clone()
+      },
     };
 
     let mut request = client
-      .request(method.clone(), parsed_url)
+      .request(method.clone(), parsed_url.clone())
       .headers(headers.into());
 
     if h3 {
@@ -309,114 +902,205 @@ impl Impit {
       request = request.timeout(timeout);
     }
 
-    request = match body {
+    request = match body.clone() {
       Some(body) => request.body(body),
       None => request
     };
 
-    let response = request.send().await;
+    let uses_proxy = !self.inner.config.proxy_url.is_empty();
 
-    if response.is_err() {
-      return Err(ErrorType::RequestError(response.err().unwrap()));
+    let mut request = request.build()
+      .map_err(|err| ErrorType::from_request_error(err, h3, uses_proxy))?;
+
+    if http1 {
+      request.extensions_mut().insert(case_map);
     }
-    
-    let response = response.unwrap();
-    
-    if !h3 {
-      if let Some(h3_engine) = self.h3_engine.as_mut() {
-        h3_engine.set_h3_support(&host, false);
-  
-        if let Some(alt_svc) = response.headers().get("Alt-Svc") {
-          let alt_svc = alt_svc.to_str().unwrap();
-          if alt_svc.contains("h3") {
-            debug!("{} supports HTTP/3 (alt-svc header), adding to Alt-Svc cache", host);
-            h3_engine.set_h3_support(&host, true);
-          }
+
+    let mut response = match client.execute(request).await {
+      Ok(mut response) => {
+        let served_by = match self.inner.config.browser {
+          Some(_) => ServedBy::Impersonated,
+          None => ServedBy::Vanilla,
+        };
+        response.extensions_mut().insert(served_by);
+        response
+      },
+      Err(err) => {
+        match &self.inner.vanilla_client {
+          Some(vanilla_client) if tls_error(&err).is_some() => {
+            debug!("Impersonated request to {} failed with a TLS error ({}), retrying without impersonation", parsed_url, err);
+            Self::send_vanilla(vanilla_client, method, parsed_url.clone(), &host, body, options, redirects).await
+              .map_err(|err| ErrorType::from_request_error(err, false, uses_proxy))?
+          },
+          _ => return Err(ErrorType::from_request_error(err, h3, uses_proxy)),
         }
       }
-    }
+    };
     
+    if let Some(reason) = tcp_fallback {
+      response.extensions_mut().insert(reason);
+    }
+
+    if let Some(h3_engine) = self.inner.h3_engine.get() {
+      h3_engine.update_from_alt_svc(&parsed_url, response.headers());
+    }
+
+    Ok(response)
+  }
+
+  /// Sends the request with the non-impersonated client. Used as a fallback for failed impersonated requests.
+  async fn send_vanilla(client: &reqwest::Client, method: Method, url: Url, host: &String, body: Option<Vec<u8>>, options: &RequestOptions, redirects: &[Url]) -> Result<Response, reqwest::Error> {
+    let headers = HttpHeaders::get_builder()
+      .with_browser(&None)
+      .with_host(host)
+      .with_url(&url)
+      .with_referrer(&options.referrer)
+      .with_redirects(redirects)
+      .with_method(&method)
+      .with_https(url.scheme() == "https")
+      .with_request_context(options.context)
+      .with_custom_headers(&options.headers)
+      .with_removed_headers(&options.removed_headers)
+      .build();
+
+    let mut request = client
+      .request(method, url)
+      .headers(headers.into());
+
+    if let Some(timeout) = options.timeout {
+      request = request.timeout(timeout);
+    }
+
+    request = match body {
+      Some(body) => request.body(body),
+      None => request
+    };
+
+    let mut response = request.send().await?;
+    response.extensions_mut().insert(ServedBy::VanillaFallback);
+
     Ok(response)
   }
 
   /// Makes a `GET` request to the specified URL.
   /// 
-  /// The `url` parameter should be a valid URL.
+  /// The `url` parameter should be a valid URL (e.g. `&str`, `String` or `Url`).
   /// Additional options like `headers`, `timeout` or HTTP/3 usage can be passed via the `RequestOptions` struct.
   /// 
   /// If the request is successful, the `reqwest::Response` struct is returned.
-  pub async fn get(&mut self, url: String, options: Option<RequestOptions>) -> Result<Response, ErrorType> {
+  pub async fn get(&self, url: impl IntoUrl, options: Option<RequestOptions>) -> Result<Response, ErrorType> {
     self.make_request(Method::GET, url, None, options).await
   }
 
   /// Makes a `HEAD` request to the specified URL.
   /// 
-  /// The `url` parameter should be a valid URL.
+  /// The `url` parameter should be a valid URL (e.g. `&str`, `String` or `Url`).
   /// Additional options like `headers`, `timeout` or HTTP/3 usage can be passed via the `RequestOptions` struct.
   /// 
   /// If the request is successful, the `reqwest::Response` struct is returned.
-  pub async fn head(&mut self, url: String, options: Option<RequestOptions>) -> Result<Response, ErrorType> {
+  pub async fn head(&self, url: impl IntoUrl, options: Option<RequestOptions>) -> Result<Response, ErrorType> {
     self.make_request(Method::HEAD, url, None, options).await
   }
 
   /// Makes an OPTIONS request to the specified URL.
   /// 
-  /// The `url` parameter should be a valid URL.
+  /// The `url` parameter should be a valid URL (e.g. `&str`, `String` or `Url`).
   /// Additional options like `headers`, `timeout` or HTTP/3 usage can be passed via the `RequestOptions` struct.
   /// 
   /// If the request is successful, the `reqwest::Response` struct is returned.
-  pub async fn options(&mut self, url: String, options: Option<RequestOptions>) -> Result<Response, ErrorType> {
+  pub async fn options(&self, url: impl IntoUrl, options: Option<RequestOptions>) -> Result<Response, ErrorType> {
     self.make_request(Method::OPTIONS, url, None, options).await
   }
 
   /// Makes a `TRACE` request to the specified URL.
   /// 
-  /// The `url` parameter should be a valid URL.
+  /// The `url` parameter should be a valid URL (e.g. `&str`, `String` or `Url`).
   /// Additional options like `headers`, `timeout` or HTTP/3 usage can be passed via the `RequestOptions` struct.
   /// 
   /// If the request is successful, the `reqwest::Response` struct is returned.
-  pub async fn trace(&mut self, url: String, options: Option<RequestOptions>) -> Result<Response, ErrorType> {
+  pub async fn trace(&self, url: impl IntoUrl, options: Option<RequestOptions>) -> Result<Response, ErrorType> {
     self.make_request(Method::TRACE, url, None, options).await
   }
 
   /// Makes a `DELETE` request to the specified URL.
   /// 
-  /// The `url` parameter should be a valid URL.
+  /// The `url` parameter should be a valid URL (e.g. `&str`, `String` or `Url`).
   /// Additional options like `headers`, `timeout` or HTTP/3 usage can be passed via the `RequestOptions` struct.
   /// 
   /// If the request is successful, the `reqwest::Response` struct is returned.
-  pub async fn delete(&mut self, url: String, options: Option<RequestOptions>) -> Result<Response, ErrorType> {
+  pub async fn delete(&self, url: impl IntoUrl, options: Option<RequestOptions>) -> Result<Response, ErrorType> {
     self.make_request(Method::DELETE, url, None, options).await
   }
 
   /// Makes a `POST` request to the specified URL.
   /// 
-  /// The `url` parameter should be a valid URL.
+  /// The `url` parameter should be a valid URL (e.g. `&str`, `String` or `Url`).
   /// Additional options like `headers`, `timeout` or HTTP/3 usage can be passed via the `RequestOptions` struct.
   /// 
   /// If the request is successful, the `reqwest::Response` struct is returned.
-  pub async fn post(&mut self, url: String, body: Option<Vec<u8>>, options: Option<RequestOptions>) -> Result<Response, ErrorType> {
+  pub async fn post(&self, url: impl IntoUrl, body: Option<Vec<u8>>, options: Option<RequestOptions>) -> Result<Response, ErrorType> {
     self.make_request(Method::POST, url, body, options).await
   }
 
   /// Makes a `PUT` request to the specified URL.
   /// 
-  /// The `url` parameter should be a valid URL.
+  /// The `url` parameter should be a valid URL (e.g. `&str`, `String` or `Url`).
   /// Additional options like `headers`, `timeout` or HTTP/3 usage can be passed via the `RequestOptions` struct.
   /// 
   /// If the request is successful, the `reqwest::Response` struct is returned.
-  pub async fn put(&mut self, url: String, body: Option<Vec<u8>>, options: Option<RequestOptions>) -> Result<Response, ErrorType> {
+  pub async fn put(&self, url: impl IntoUrl, body: Option<Vec<u8>>, options: Option<RequestOptions>) -> Result<Response, ErrorType> {
     self.make_request(Method::PUT, url, body, options).await
   }
 
   /// Makes a `PATCH` request to the specified URL.
   /// 
-  /// The `url` parameter should be a valid URL.
+  /// The `url` parameter should be a valid URL (e.g. `&str`, `String` or `Url`).
   /// Additional options like `headers`, `timeout` or HTTP/3 usage can be passed via the `RequestOptions` struct.
   /// 
   /// If the request is successful, the `reqwest::Response` struct is returned.
-  pub async fn patch(&mut self, url: String, body: Option<Vec<u8>>, options: Option<RequestOptions>) -> Result<Response, ErrorType> {
+  pub async fn patch(&self, url: impl IntoUrl, body: Option<Vec<u8>>, options: Option<RequestOptions>) -> Result<Response, ErrorType> {
     self.make_request(Method::PATCH, url, body, options).await
   }
+}
+
+/// Returns the `rustls` error that caused the request failure (e.g. a failed handshake or a rejected certificate), if any.
+/// 
+/// Walks the `source()` chain of the `reqwest::Error`, as the `rustls` errors are usually wrapped in `std::io::Error`.
+fn tls_error(err: &reqwest::Error) -> Option<rustls::Error> {
+  let mut source: Option<&(dyn std::error::Error + 'static)> = std::error::Error::source(err);
+
+  while let Some(inner) = source {
+    if let Some(tls_error) = inner.downcast_ref::<rustls::Error>() {
+      return Some(tls_error.clone());
+    }
+
+    if let Some(io_error) = inner.downcast_ref::<std::io::Error>() {
+      if let Some(tls_error) = io_error.get_ref().and_then(|e| e.downcast_ref::<rustls::Error>()) {
+        return Some(tls_error.clone());
+      }
+    }
+
+    source = inner.source();
+  }
 
-}
\ No newline at end of file
+  None
+}
+
+/// The headers describing the request body, dropped when a redirect changes the method to `GET`.
+const REQUEST_BODY_HEADERS: &[&str] = &["content-type", "content-encoding", "content-language", "content-location", "content-length"];
+
+/// Returns the redirect target of the response, resolved against the request URL.
+fn redirect_location(response: &Response, url: &Url) -> Option<Url> {
+  let redirect = matches!(
+    response.status(),
+    StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND | StatusCode::SEE_OTHER | StatusCode::TEMPORARY_REDIRECT | StatusCode::PERMANENT_REDIRECT
+  );
+
+  if !redirect {
+    return None;
+  }
+
+  let location = response.headers().get("location")?.to_str().ok()?;
+
+  url.join(location).ok()
+}
@@ -6,12 +6,12 @@
 //! 
 //! ```rust
 //! use impit::impit::Impit;
-//! use impit::emulation::Browser;
+//! use impit::emulation::{Browser, FirefoxVersion};
 //!  
 //! #[tokio::main]
 //! async fn main() {
-//!    let mut impit = Impit::builder()
-//!        .with_browser(Browser::Firefox)
+//!    let impit = Impit::builder()
+//!        .with_browser(Browser::Firefox(FirefoxVersion::V133))
 //!        .with_http3()
 //!        .build();
 //! 
@@ -57,11 +57,13 @@
 //! Note that you also have to build your project with `rustflags = "--cfg reqwest_unstable"`, otherwise, the build will also fail.
 //! This is because `impit` uses unstable features of `reqwest` (namely `http3` support), which are not available in the stable version of the library.
 
-#![deny(unused_crate_dependencies)]
-mod http_headers;
+// Dev-dependencies are only used by the integration tests, the lint would flag them in the unit test builds.
+#![cfg_attr(not(test), deny(unused_crate_dependencies))]
+pub(crate) mod http_headers;
 mod tls;
 mod response_parsing;
 
+pub(crate) mod http2;
 pub(crate) mod http3;
 
 /// Main module that contains the `Impit` struct and its methods.
@@ -70,20 +72,17 @@ pub mod impit;
 /// Customizing request options.
 pub mod request;
 
+/// Configuring the DNS lookups.
+pub mod dns;
+
+/// Redirect handling and the redirect history of the responses.
+pub mod redirect;
+
+/// Parsing the `Alt-Svc` header, advertising the alternative services (e.g. HTTP/3).
+pub mod alt_svc;
+
 /// Contains browser emulation-related types and functions.
-pub mod emulation {
-  
-  /// The `Browser` enum is used to specify the browser that should be impersonated.
-  /// 
-  /// It can be passed as a parameter to [`ImpitBuilder::with_browser`](crate::impit::ImpitBuilder::with_browser) 
-  /// to use the browser emulation with the built [`Impit`](crate::impit::Impit) instance.
-  #[derive(PartialEq, Debug, Clone, Copy, Default)]
-  pub enum Browser {
-    #[default]
-    Chrome,
-    Firefox,
-  }
-}
+pub mod emulation;
 
 /// Various utility functions and types.
 pub mod utils {
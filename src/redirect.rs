@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use reqwest::header::HeaderMap;
+use reqwest::{Response, StatusCode};
+use url::Url;
+
+/// A redirect response received while following the redirects of a request.
+#[derive(Debug, Clone)]
+pub struct RedirectHop {
+  /// The URL of the request that was redirected.
+  pub url: Url,
+  /// The redirect status code, e.g. `302`.
+  pub status: StatusCode,
+  /// The headers of the redirect response, including `Location` and `Set-Cookie`.
+  pub headers: HeaderMap,
+  /// The time from sending the request to receiving the redirect response headers.
+  pub elapsed: Duration,
+}
+
+/// A redirect about to be followed, passed to the [`RedirectBehavior::Custom`](crate::impit::RedirectBehavior::Custom) callback.
+#[derive(Debug)]
+pub struct RedirectAttempt<'a> {
+  /// The redirects received so far. The last one is the redirect being decided on.
+  pub history: &'a [RedirectHop],
+  /// The URL the redirect points to.
+  pub next: &'a Url,
+}
+
+impl RedirectAttempt<'_> {
+  /// The redirect being decided on.
+  pub fn hop(&self) -> &RedirectHop {
+    self.history.last().unwrap()
+  }
+}
+
+/// The decision of the [`RedirectBehavior::Custom`](crate::impit::RedirectBehavior::Custom) callback.
+#[derive(Debug)]
+pub enum RedirectAction {
+  /// Follow the redirect.
+  Follow,
+  /// Stop and return the redirect response.
+  Stop,
+  /// Fail the request with the [`ErrorType::RedirectRejected`](crate::impit::ErrorType::RedirectRejected) error.
+  Error(Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// The redirects of a response, stored in the response extensions.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Redirects(pub(crate) Vec<RedirectHop>);
+
+/// Exposes the redirects followed by [`Impit`](crate::impit::Impit) on the `reqwest::Response`.
+///
+/// ### Example
+/// ```rust
+/// use impit::redirect::RedirectHistory;
+///
+/// let response = impit.get("https://example.com/login", None).await.unwrap();
+///
+/// for hop in response.redirects() {
+///   println!("{} {} ({:?})", hop.status, hop.url, hop.headers.get("location"));
+/// }
+/// ```
+pub trait RedirectHistory {
+  /// Returns the redirect responses received before the final response, in order.
+  fn redirects(&self) -> &[RedirectHop];
+
+  /// Returns all the URLs requested, from the original URL to the URL of the final response.
+  fn url_chain(&self) -> Vec<&Url>;
+}
+
+impl RedirectHistory for Response {
+  fn redirects(&self) -> &[RedirectHop] {
+    self.extensions().get::<Redirects>()
+      .map(|redirects| redirects.0.as_slice())
+      .unwrap_or_default()
+  }
+
+  fn url_chain(&self) -> Vec<&Url> {
+    self.redirects().iter()
+      .map(|hop| &hop.url)
+      .chain(std::iter::once(self.url()))
+      .collect()
+  }
+}
@@ -1,4 +1,13 @@
-use std::{collections::HashMap, time::Duration};
+use std::time::Duration;
+
+use reqwest::header::{HeaderName, HeaderValue};
+use reqwest::Response;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::impit::{ErrorType, Impit};
+
+pub use reqwest::Method;
 
 /// A struct that holds the request options.
 /// 
@@ -7,8 +16,24 @@ use std::{collections::HashMap, time::Duration};
 /// Used by the [`Impit`](crate::impit::Impit) struct's methods.
 #[derive(Debug, Clone)]
 pub struct RequestOptions {
-  /// A `HashMap` that holds custom HTTP headers. These are added to the default headers and should never overwrite them.
-  pub headers: HashMap<String, String>,
+  /// Custom HTTP headers, in the order they should be sent. A header name can be repeated to send multiple values.
+  /// 
+  /// All the values of a repeated name are sent together, at the position of its first value.
+  /// 
+  /// See [`HeaderPosition`] for how the custom headers are merged with the default headers of the impersonated browser.
+  pub headers: Vec<CustomHeader>,
+  /// Names of the default browser headers that shouldn't be sent with this request (case-insensitive).
+  pub removed_headers: Vec<String>,
+  /// The kind of request the browser would make, e.g. a page navigation or a `fetch()` call.
+  /// 
+  /// Drives the `Accept`, `Sec-Fetch-*` and `Priority` headers. Defaults to [`RequestContext::Navigate`].
+  pub context: RequestContext,
+  /// The URL of the page that initiated the request, e.g. the page with the clicked link or the `fetch()` call.
+  /// 
+  /// Used for computing the `Sec-Fetch-Site`, `Referer` and `Origin` headers the way the browser would.
+  /// Without a referrer, no `Referer` is sent. Navigations are treated as typed into the address bar (`Sec-Fetch-Site: none`),
+  /// the `fetch()` calls and fonts as made by a page of the requested origin, and the other subresources as cross-site.
+  pub referrer: Option<Url>,
   /// The timeout for the request. This option overrides the global [`Impit`] timeout.
   pub timeout: Option<Duration>,
   /// Enforce the use of HTTP/3 for this request. This will cause broken responses from servers that don't support HTTP/3.
@@ -20,9 +45,277 @@ pub struct RequestOptions {
 impl Default for RequestOptions {
   fn default() -> Self {
     RequestOptions {
-      headers: HashMap::new(),
+      headers: vec![],
+      removed_headers: vec![],
+      context: RequestContext::Navigate,
+      referrer: None,
       timeout: None,
       http3_prior_knowledge: false,
     }
   }
-}
\ No newline at end of file
+}
+
+/// The kind of request the browser would make.
+/// 
+/// Browsers send different `Accept`, `Sec-Fetch-*` and `Priority` headers for top-level navigations, 
+/// `fetch()` calls and the subresources of a page. Sending the navigation headers with e.g. a JSON API request is easy to detect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RequestContext {
+  /// A top-level navigation, e.g. a link clicked by the user.
+  #[default]
+  Navigate,
+  /// A `fetch()` or `XMLHttpRequest` call.
+  Fetch,
+  /// An image loaded with the `<img>` tag.
+  Image,
+  /// A script loaded with the `<script>` tag.
+  Script,
+  /// A stylesheet loaded with the `<link rel="stylesheet">` tag.
+  Style,
+  /// A web font loaded from a stylesheet.
+  Font,
+  /// A document loaded in an `<iframe>`.
+  Iframe,
+}
+
+/// Describes where a custom header is placed relative to the default headers of the impersonated browser.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum HeaderPosition {
+  /// Replaces the value of the default header with the same name, keeping its position.
+  /// If the browser doesn't send such header, the custom header is appended after the default headers.
+  #[default]
+  Default,
+  /// Inserted before all the default headers.
+  Start,
+  /// Appended after all the default headers.
+  End,
+  /// Inserted right before the header with the given name (case-insensitive). Appended at the end if there's no such header.
+  Before(String),
+  /// Inserted right after the header with the given name (case-insensitive). Appended at the end if there's no such header.
+  After(String),
+}
+
+/// A custom HTTP header sent with a request.
+/// 
+/// ### Example
+/// ```rust
+/// let options = RequestOptions {
+///   headers: vec![
+///     CustomHeader::new("accept", "application/json"),
+///     CustomHeader::new("x-trace", "1").with_position(HeaderPosition::After("user-agent".to_string())),
+///     CustomHeader::new("x-tag", "a"),
+///     CustomHeader::new("x-tag", "b"),
+///   ],
+///   removed_headers: vec!["priority".to_string()],
+///   ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomHeader {
+  pub name: String,
+  pub value: String,
+  pub position: HeaderPosition,
+}
+
+impl CustomHeader {
+  /// Creates a custom header with the [`HeaderPosition::Default`] position.
+  pub fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
+    CustomHeader {
+      name: name.into(),
+      value: value.into(),
+      position: HeaderPosition::Default,
+    }
+  }
+
+  /// Sets the position of the header relative to the default browser headers.
+  pub fn with_position(mut self, position: HeaderPosition) -> Self {
+    self.position = position;
+    self
+  }
+
+  /// Checks that the header can be sent, i.e. the name is a valid header name and the value doesn't contain control characters.
+  pub(crate) fn validate(&self) -> Result<(), ErrorType> {
+    if HeaderName::from_bytes(self.name.as_bytes()).is_err() || HeaderValue::from_str(&self.value).is_err() {
+      return Err(ErrorType::InvalidHeader { name: self.name.to_owned(), value: self.value.to_owned() });
+    }
+
+    Ok(())
+  }
+}
+
+/// Types that can be converted to a request URL - `&str`, `String` and `Url`.
+pub trait IntoUrl {
+  /// Parses the value into a `Url`.
+  fn into_url(self) -> Result<Url, ErrorType>;
+}
+
+impl IntoUrl for Url {
+  fn into_url(self) -> Result<Url, ErrorType> {
+    Ok(self)
+  }
+}
+
+impl IntoUrl for &Url {
+  fn into_url(self) -> Result<Url, ErrorType> {
+    Ok(self.clone())
+  }
+}
+
+impl IntoUrl for &str {
+  fn into_url(self) -> Result<Url, ErrorType> {
+    Url::parse(self).map_err(|_| ErrorType::UrlParsingError)
+  }
+}
+
+impl IntoUrl for String {
+  fn into_url(self) -> Result<Url, ErrorType> {
+    self.as_str().into_url()
+  }
+}
+
+impl IntoUrl for &String {
+  fn into_url(self) -> Result<Url, ErrorType> {
+    self.as_str().into_url()
+  }
+}
+
+/// A builder for a single request, created with [`Impit::request`].
+/// 
+/// The errors (e.g. an invalid URL) are deferred and returned from [`RequestBuilder::send`].
+pub struct RequestBuilder<'a> {
+  impit: &'a Impit,
+  method: Method,
+  url: Result<Url, ErrorType>,
+  body: Option<Vec<u8>>,
+  options: RequestOptions,
+  error: Option<ErrorType>,
+}
+
+impl<'a> RequestBuilder<'a> {
+  pub(crate) fn new(impit: &'a Impit, method: Method, url: impl IntoUrl) -> Self {
+    RequestBuilder {
+      impit,
+      method,
+      url: url.into_url(),
+      body: None,
+      options: RequestOptions::default(),
+      error: None,
+    }
+  }
+
+  /// Keeps the first error, it's returned by [`RequestBuilder::send`].
+  fn set_error(&mut self, err: ErrorType) {
+    self.error.get_or_insert(err);
+  }
+
+  /// Adds the header, or records [`ErrorType::InvalidHeader`] if the name or the value isn't valid.
+  fn push_header(&mut self, header: CustomHeader) {
+    match header.validate() {
+      Ok(()) => self.options.headers.push(header),
+      Err(err) => self.set_error(err),
+    }
+  }
+
+  /// Adds a custom HTTP header to the request.
+  /// 
+  /// Replaces the browser's default header with the same name, in its position. 
+  /// Calling this repeatedly with the same name sends multiple values.
+  /// 
+  /// An invalid header name or value makes [`RequestBuilder::send`] fail with [`ErrorType::InvalidHeader`].
+  pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+    self.push_header(CustomHeader::new(name, value));
+    self
+  }
+
+  /// Adds a custom HTTP header to the request, at the given position relative to the default browser headers.
+  /// 
+  /// An invalid header name or value makes [`RequestBuilder::send`] fail with [`ErrorType::InvalidHeader`].
+  pub fn header_at(mut self, name: impl Into<String>, value: impl Into<String>, position: HeaderPosition) -> Self {
+    self.push_header(CustomHeader::new(name, value).with_position(position));
+    self
+  }
+
+  /// Removes a default header of the impersonated browser from the request.
+  pub fn remove_header(mut self, name: impl Into<String>) -> Self {
+    self.options.removed_headers.push(name.into());
+    self
+  }
+
+  /// Appends the key-value pairs to the query string of the URL.
+  pub fn query<I, K, V>(mut self, pairs: I) -> Self
+  where
+    I: IntoIterator,
+    I::Item: std::borrow::Borrow<(K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+  {
+    if let Ok(url) = &mut self.url {
+      url.query_pairs_mut().extend_pairs(pairs);
+    }
+    self
+  }
+
+  /// Sets the request body.
+  pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+    self.body = Some(body.into());
+    self
+  }
+
+  /// Serializes the value to JSON and uses it as the request body.
+  /// 
+  /// Also sets the `Content-Type` header to `application/json`, unless it was set already.
+  pub fn json<T: Serialize + ?Sized>(mut self, json: &T) -> Self {
+    match serde_json::to_vec(json) {
+      Ok(body) => {
+        if !self.options.headers.iter().any(|header| header.name.eq_ignore_ascii_case("content-type")) {
+          self.options.headers.push(CustomHeader::new("content-type", "application/json"));
+        }
+        self.body = Some(body);
+      },
+      Err(err) => self.set_error(ErrorType::JsonSerializationError(err)),
+    }
+    self
+  }
+
+  /// Sets the kind of request the browser would make. See [`RequestContext`].
+  pub fn context(mut self, context: RequestContext) -> Self {
+    self.options.context = context;
+    self
+  }
+
+  /// Sets the URL of the page that initiated the request. See [`RequestOptions::referrer`].
+  pub fn referrer(mut self, referrer: impl IntoUrl) -> Self {
+    match referrer.into_url() {
+      Ok(referrer) => self.options.referrer = Some(referrer),
+      Err(err) => self.set_error(err),
+    }
+    self
+  }
+
+  /// Sets the timeout for the request, overriding the [`Impit`] timeout.
+  pub fn timeout(mut self, timeout: Duration) -> Self {
+    self.options.timeout = Some(timeout);
+    self
+  }
+
+  /// Enforces the use of HTTP/3 for this request. See [`RequestOptions::http3_prior_knowledge`].
+  pub fn http3_prior_knowledge(mut self, http3_prior_knowledge: bool) -> Self {
+    self.options.http3_prior_knowledge = http3_prior_knowledge;
+    self
+  }
+
+  /// Sends the request.
+  /// 
+  /// Fails with the first error recorded while building the request (e.g. an invalid header), without sending anything.
+  pub async fn send(self) -> Result<Response, ErrorType> {
+    // The URL is parsed when the builder is created, before any other error can be recorded.
+    let url = self.url?;
+
+    if let Some(err) = self.error {
+      return Err(err);
+    }
+
+    self.impit.make_request(self.method, url, self.body, Some(self.options)).await
+  }
+}
@@ -0,0 +1,286 @@
+use std::path::Path;
+
+use crate::emulation::{BrowserFamily, TlsProfile};
+
+use super::{is_supported_cipher_suite, is_supported_group, pcap};
+
+const RECORD_TYPE_HANDSHAKE: u8 = 0x16;
+const HANDSHAKE_TYPE_CLIENT_HELLO: u8 = 0x01;
+
+const EXTENSION_SERVER_NAME: u16 = 0x0000;
+const EXTENSION_SUPPORTED_GROUPS: u16 = 0x000a;
+const EXTENSION_EC_POINT_FORMATS: u16 = 0x000b;
+const EXTENSION_SIGNATURE_ALGORITHMS: u16 = 0x000d;
+const EXTENSION_ALPN: u16 = 0x0010;
+const EXTENSION_SUPPORTED_VERSIONS: u16 = 0x002b;
+const EXTENSION_KEY_SHARE: u16 = 0x0033;
+
+/// Error types that can be returned when parsing a ClientHello message.
+#[derive(Debug)]
+pub enum ClientHelloError {
+  /// The capture file couldn't be read.
+  IoError(std::io::Error),
+  /// The data ends before the end of the ClientHello message.
+  Truncated,
+  /// The data is not a TLS handshake record (or a handshake message) containing a ClientHello.
+  NotAClientHello,
+  /// The capture file is neither a pcap nor a pcapng file.
+  UnsupportedCaptureFormat,
+  /// The capture file doesn't contain any ClientHello message.
+  NoClientHelloFound,
+}
+
+/// Checks whether the value is one of the reserved GREASE values (RFC 8701).
+pub fn is_grease(value: u16) -> bool {
+  value & 0x0f0f == 0x0a0a && value >> 8 == value & 0xff
+}
+
+fn without_grease(values: &[u16]) -> Vec<u16> {
+  values.iter().copied().filter(|value| !is_grease(*value)).collect()
+}
+
+/// A parsed TLS ClientHello message.
+///
+/// All the lists are kept in the order they were sent, including the GREASE values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientHello {
+  /// The `legacy_version` field of the ClientHello (`0x0303` for TLS 1.2 and 1.3).
+  pub legacy_version: u16,
+  pub cipher_suites: Vec<u16>,
+  /// Extension types, in the order they were sent.
+  pub extensions: Vec<u16>,
+  pub server_name: Option<String>,
+  pub supported_groups: Vec<u16>,
+  pub ec_point_formats: Vec<u8>,
+  pub signature_algorithms: Vec<u16>,
+  pub alpn: Vec<String>,
+  pub supported_versions: Vec<u16>,
+  /// Named groups the client sent key shares for.
+  pub key_shares: Vec<u16>,
+}
+
+impl ClientHello {
+  /// Parses a ClientHello from raw bytes.
+  ///
+  /// The bytes can either contain the TLS record(s) (starting with the `0x16` content type), as captured on the wire,
+  /// or just the handshake message (starting with the `0x01` handshake type).
+  pub fn parse(bytes: &[u8]) -> Result<Self, ClientHelloError> {
+    let handshake = match bytes.first() {
+      Some(&RECORD_TYPE_HANDSHAKE) => reassemble_handshake(bytes)?,
+      Some(_) => bytes.to_vec(),
+      None => return Err(ClientHelloError::Truncated),
+    };
+
+    let mut reader = Reader::new(&handshake);
+
+    if reader.u8()? != HANDSHAKE_TYPE_CLIENT_HELLO {
+      return Err(ClientHelloError::NotAClientHello);
+    }
+
+    let length = reader.u24()? as usize;
+    let mut body = reader.sub(length)?;
+
+    let mut client_hello = ClientHello {
+      legacy_version: body.u16()?,
+      cipher_suites: vec![],
+      extensions: vec![],
+      server_name: None,
+      supported_groups: vec![],
+      ec_point_formats: vec![],
+      signature_algorithms: vec![],
+      alpn: vec![],
+      supported_versions: vec![],
+      key_shares: vec![],
+    };
+
+    // random
+    body.bytes(32)?;
+    // legacy_session_id
+    body.vec8()?;
+    client_hello.cipher_suites = body.vec16()?.u16_list()?;
+    // legacy_compression_methods
+    body.vec8()?;
+
+    if body.is_empty() {
+      return Ok(client_hello);
+    }
+
+    let mut extensions = body.vec16()?;
+    while !extensions.is_empty() {
+      let extension_type = extensions.u16()?;
+      let mut data = extensions.vec16()?;
+
+      client_hello.extensions.push(extension_type);
+
+      match extension_type {
+        EXTENSION_SERVER_NAME => {
+          let mut names = data.vec16()?;
+          while !names.is_empty() {
+            let name_type = names.u8()?;
+            let name = names.vec16()?;
+
+            // host_name
+            if name_type == 0 {
+              client_hello.server_name = Some(String::from_utf8_lossy(name.rest()).to_string());
+            }
+          }
+        },
+        EXTENSION_SUPPORTED_GROUPS => client_hello.supported_groups = data.vec16()?.u16_list()?,
+        EXTENSION_EC_POINT_FORMATS => client_hello.ec_point_formats = data.vec8()?.rest().to_vec(),
+        EXTENSION_SIGNATURE_ALGORITHMS => client_hello.signature_algorithms = data.vec16()?.u16_list()?,
+        EXTENSION_ALPN => {
+          let mut protocols = data.vec16()?;
+          while !protocols.is_empty() {
+            let protocol = protocols.vec8()?;
+            client_hello.alpn.push(String::from_utf8_lossy(protocol.rest()).to_string());
+          }
+        },
+        EXTENSION_SUPPORTED_VERSIONS => client_hello.supported_versions = data.vec8()?.u16_list()?,
+        EXTENSION_KEY_SHARE => {
+          let mut key_shares = data.vec16()?;
+          while !key_shares.is_empty() {
+            client_hello.key_shares.push(key_shares.u16()?);
+            key_shares.vec16()?;
+          }
+        },
+        _ => {},
+      }
+    }
+
+    Ok(client_hello)
+  }
+
+  /// Reads the first ClientHello from a pcap or pcapng capture file.
+  ///
+  /// The capture should contain the TCP traffic of the browser (e.g. `tcpdump -w capture.pcap port 443`).
+  /// QUIC (HTTP/3) handshakes are not supported.
+  pub fn from_pcap<P: AsRef<Path>>(path: P) -> Result<Self, ClientHelloError> {
+    let capture = std::fs::read(path).map_err(ClientHelloError::IoError)?;
+    let record = pcap::find_client_hello(&capture)?;
+
+    Self::parse(&record)
+  }
+
+  /// Converts the ClientHello to the TLS part of a [`BrowserProfile`](crate::emulation::BrowserProfile).
+  ///
+  /// The GREASE values are left out, as the browser emulator generates them on its own.
+  /// The cipher suites and groups `rustls` can't use are left out as well (e.g. the CBC suites, the `TLS_EMPTY_RENEGOTIATION_INFO_SCSV`
+  /// value, `x448` or `X25519MLKEM768`), so the profile passes [`BrowserProfile::validate`](crate::emulation::BrowserProfile::validate).
+  /// The left out values are returned by [`ClientHello::unsupported_values`].
+  /// `family` and `version` select the emulator used for the parts of the ClientHello that aren't described by the profile.
+  ///
+  /// The key shares, signature algorithms and the extension order aren't copied, the emulator can't apply them
+  /// (see [`TlsProfile`]). They are sent as the emulated browser release sends them.
+  pub fn to_tls_profile(&self, family: BrowserFamily, version: u16) -> TlsProfile {
+    TlsProfile {
+      family,
+      version,
+      cipher_suites: self.cipher_suites.iter().copied().filter(|id| is_supported_cipher_suite(*id)).collect(),
+      supported_groups: self.supported_groups.iter().copied().filter(|id| is_supported_group(*id)).collect(),
+      key_shares: vec![],
+      signature_algorithms: vec![],
+      extensions_order: vec![],
+      alpn: self.alpn.clone(),
+    }
+  }
+
+  /// Returns the cipher suites and the groups (without GREASE) [`ClientHello::to_tls_profile`] leaves out of the profile.
+  pub fn unsupported_values(&self) -> (Vec<u16>, Vec<u16>) {
+    let cipher_suites = without_grease(&self.cipher_suites).into_iter().filter(|id| !is_supported_cipher_suite(*id)).collect();
+    let groups = without_grease(&self.supported_groups).into_iter().filter(|id| !is_supported_group(*id)).collect();
+
+    (cipher_suites, groups)
+  }
+}
+
+/// Concatenates the fragments of the handshake records, until the whole first handshake message is available.
+fn reassemble_handshake(bytes: &[u8]) -> Result<Vec<u8>, ClientHelloError> {
+  let mut reader = Reader::new(bytes);
+  let mut handshake: Vec<u8> = vec![];
+
+  loop {
+    if handshake.len() >= 4 {
+      let length = u32::from_be_bytes([0, handshake[1], handshake[2], handshake[3]]) as usize;
+
+      if handshake.len() >= length + 4 {
+        return Ok(handshake);
+      }
+    }
+
+    if reader.u8()? != RECORD_TYPE_HANDSHAKE {
+      return Err(ClientHelloError::NotAClientHello);
+    }
+
+    // legacy_record_version
+    reader.u16()?;
+    let fragment = reader.vec16()?;
+    handshake.extend_from_slice(fragment.rest());
+  }
+}
+
+/// A cursor over a byte slice, reading the TLS presentation language primitives.
+struct Reader<'a> {
+  data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+  fn new(data: &'a [u8]) -> Self {
+    Reader { data }
+  }
+
+  fn is_empty(&self) -> bool {
+    self.data.is_empty()
+  }
+
+  fn rest(&self) -> &'a [u8] {
+    self.data
+  }
+
+  fn bytes(&mut self, length: usize) -> Result<&'a [u8], ClientHelloError> {
+    if self.data.len() < length {
+      return Err(ClientHelloError::Truncated);
+    }
+
+    let (bytes, rest) = self.data.split_at(length);
+    self.data = rest;
+    Ok(bytes)
+  }
+
+  fn u8(&mut self) -> Result<u8, ClientHelloError> {
+    Ok(self.bytes(1)?[0])
+  }
+
+  fn u16(&mut self) -> Result<u16, ClientHelloError> {
+    let bytes = self.bytes(2)?;
+    Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+  }
+
+  fn u24(&mut self) -> Result<u32, ClientHelloError> {
+    let bytes = self.bytes(3)?;
+    Ok(u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]))
+  }
+
+  fn sub(&mut self, length: usize) -> Result<Reader<'a>, ClientHelloError> {
+    Ok(Reader::new(self.bytes(length)?))
+  }
+
+  /// Reads a vector with a 1-byte length prefix.
+  fn vec8(&mut self) -> Result<Reader<'a>, ClientHelloError> {
+    let length = self.u8()? as usize;
+    self.sub(length)
+  }
+
+  /// Reads a vector with a 2-byte length prefix.
+  fn vec16(&mut self) -> Result<Reader<'a>, ClientHelloError> {
+    let length = self.u16()? as usize;
+    self.sub(length)
+  }
+
+  fn u16_list(&mut self) -> Result<Vec<u16>, ClientHelloError> {
+    let mut values = vec![];
+    while !self.is_empty() {
+      values.push(self.u16()?);
+    }
+    Ok(values)
+  }
+}
@@ -0,0 +1,142 @@
+use md5::Md5;
+use sha2::{Digest, Sha256};
+
+use super::client_hello::{is_grease, ClientHello};
+
+const EXTENSION_SERVER_NAME: u16 = 0x0000;
+const EXTENSION_ALPN: u16 = 0x0010;
+
+/// TLS fingerprints of a ClientHello message.
+///
+/// Note that Chrome shuffles the order of the TLS extensions for every connection, which changes the JA3 fingerprint.
+/// To compare the Chrome fingerprints, use JA3N or JA4, which sort the extensions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsFingerprint {
+  /// The full JA3 string (`version,ciphers,extensions,groups,point formats`).
+  pub ja3: String,
+  /// MD5 hash of the JA3 string, as usually reported by the fingerprinting services.
+  pub ja3_hash: String,
+  /// The JA3 string with the extensions sorted.
+  pub ja3n: String,
+  /// MD5 hash of the JA3N string.
+  pub ja3n_hash: String,
+  /// The JA4 fingerprint (e.g. `t13d1516h2_8daaf6152771_e5627efa2ab1`).
+  pub ja4: String,
+}
+
+impl TlsFingerprint {
+  /// Computes the fingerprints of a ClientHello sent over TCP.
+  pub fn from_client_hello(client_hello: &ClientHello) -> Self {
+    let ja3 = ja3_string(client_hello, false);
+    let ja3n = ja3_string(client_hello, true);
+
+    TlsFingerprint {
+      ja3_hash: hex(&Md5::digest(ja3.as_bytes())),
+      ja3,
+      ja3n_hash: hex(&Md5::digest(ja3n.as_bytes())),
+      ja3n,
+      ja4: ja4(client_hello),
+    }
+  }
+}
+
+fn without_grease(values: &[u16]) -> Vec<u16> {
+  values.iter().copied().filter(|value| !is_grease(*value)).collect()
+}
+
+fn join<T: ToString>(values: &[T], separator: &str) -> String {
+  values.iter().map(|value| value.to_string()).collect::<Vec<_>>().join(separator)
+}
+
+fn hex(bytes: &[u8]) -> String {
+  bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Builds the JA3 string, see https://github.com/salesforce/ja3.
+fn ja3_string(client_hello: &ClientHello, sort_extensions: bool) -> String {
+  let mut extensions = without_grease(&client_hello.extensions);
+
+  if sort_extensions {
+    extensions.sort();
+  }
+
+  format!(
+    "{},{},{},{},{}",
+    client_hello.legacy_version,
+    join(&without_grease(&client_hello.cipher_suites), "-"),
+    join(&extensions, "-"),
+    join(&without_grease(&client_hello.supported_groups), "-"),
+    join(&client_hello.ec_point_formats, "-"),
+  )
+}
+
+/// Returns the first 12 characters of the SHA256 hash of the value, or zeros for empty values.
+fn truncated_sha256(value: &str) -> String {
+  if value.is_empty() {
+    return String::from("000000000000");
+  }
+
+  hex(&Sha256::digest(value.as_bytes()))[..12].to_string()
+}
+
+fn hex_list(values: &[u16]) -> String {
+  values.iter().map(|value| format!("{:04x}", value)).collect::<Vec<_>>().join(",")
+}
+
+/// Builds the JA4 fingerprint, see https://github.com/FoxIO-LLC/ja4/blob/main/technical_details/JA4.md.
+fn ja4(client_hello: &ClientHello) -> String {
+  let version = without_grease(&client_hello.supported_versions)
+    .into_iter()
+    .max()
+    .unwrap_or(client_hello.legacy_version);
+
+  let version = match version {
+    0x0304 => "13",
+    0x0303 => "12",
+    0x0302 => "11",
+    0x0301 => "10",
+    0x0300 => "s3",
+    _ => "00",
+  };
+
+  let sni = if client_hello.extensions.contains(&EXTENSION_SERVER_NAME) { "d" } else { "i" };
+
+  let mut cipher_suites = without_grease(&client_hello.cipher_suites);
+  let mut extensions = without_grease(&client_hello.extensions);
+
+  let alpn = match client_hello.alpn.first().map(|protocol| protocol.as_bytes()) {
+    Some(&[first, .., last]) | Some(&[first @ last]) if first.is_ascii_alphanumeric() && last.is_ascii_alphanumeric() => {
+      format!("{}{}", first as char, last as char)
+    },
+    Some(&[first, .., last]) | Some(&[first @ last]) => {
+      let (first, last) = (format!("{:02x}", first), format!("{:02x}", last));
+      format!("{}{}", &first[..1], &last[1..])
+    },
+    _ => String::from("00"),
+  };
+
+  let ja4_a = format!(
+    "t{}{}{:02}{:02}{}",
+    version,
+    sni,
+    cipher_suites.len().min(99),
+    extensions.len().min(99),
+    alpn,
+  );
+
+  cipher_suites.sort();
+  let ja4_b = truncated_sha256(&hex_list(&cipher_suites));
+
+  extensions.retain(|extension| *extension != EXTENSION_SERVER_NAME && *extension != EXTENSION_ALPN);
+  extensions.sort();
+
+  let ja4_c = if extensions.is_empty() {
+    truncated_sha256("")
+  } else if client_hello.signature_algorithms.is_empty() {
+    truncated_sha256(&hex_list(&extensions))
+  } else {
+    truncated_sha256(&format!("{}_{}", hex_list(&extensions), hex_list(&client_hello.signature_algorithms)))
+  };
+
+  format!("{}_{}_{}", ja4_a, ja4_b, ja4_c)
+}
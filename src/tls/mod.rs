@@ -1,15 +1,22 @@
 mod statics;
 mod ffdhe;
+mod client_hello;
+mod fingerprint;
+mod pcap;
+
+pub use client_hello::{ClientHello, ClientHelloError};
+pub use fingerprint::TlsFingerprint;
 
 use std::sync::Arc;
 
-use crate::emulation::Browser;
+use crate::emulation::{BrowserFamily, TlsProfile};
+use log::debug;
 use reqwest::Version;
 use rustls::client::danger::NoVerifier;
-use rustls::client::{BrowserEmulator as RusTLSBrowser, BrowserType, EchGreaseConfig};
-use rustls::crypto::aws_lc_rs::kx_group::{SECP256R1, SECP384R1, X25519};
-use rustls::crypto::CryptoProvider;
-use rustls::RootCertStore;
+use rustls::client::{BrowserEmulator as RusTLSBrowser, BrowserType, EchConfig, EchGreaseConfig};
+use rustls::crypto::{aws_lc_rs, CryptoProvider, SupportedKxGroup};
+use rustls::pki_types::{EchConfigListBytes, ServerName};
+use rustls::{RootCertStore, SupportedCipherSuite};
 
 pub struct TlsConfig {}
 
@@ -19,11 +26,12 @@ impl TlsConfig {
   }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct TlsConfigBuilder {
-  browser: Option<Browser>,
+  browser: Option<TlsProfile>,
   max_http_version: Version,
   ignore_tls_errors: bool,
+  ech_config: Option<Vec<u8>>,
 }
 
 impl Default for TlsConfigBuilder {
@@ -32,20 +40,28 @@ impl Default for TlsConfigBuilder {
           browser: None,
           max_http_version: Version::HTTP_2,
           ignore_tls_errors: false,
+          ech_config: None,
       }
   }
 }
 
 impl TlsConfigBuilder {
-  fn get_ech_mode(self) -> rustls::client::EchMode {
+  /// Uses the ECH configuration of the server if there is one `rustls` can use, sends the GREASE ECH extension otherwise.
+  fn get_ech_mode(&self) -> Result<rustls::client::EchMode, rustls::Error> {
+      if let Some(ech_config) = &self.ech_config {
+          match EchConfig::new(EchConfigListBytes::from(ech_config.clone()), aws_lc_rs::hpke::ALL_SUPPORTED_SUITES) {
+              Ok(ech_config) => return Ok(ech_config.into()),
+              Err(err) => debug!("The ECH configuration can't be used ({}), sending the GREASE ECH extension", err),
+          }
+      }
+
       let (public_key, _) = statics::GREASE_HPKE_SUITE
-          .generate_key_pair()
-          .unwrap();
+          .generate_key_pair()?;
       
-      EchGreaseConfig::new(statics::GREASE_HPKE_SUITE, public_key).into()
+      Ok(EchGreaseConfig::new(statics::GREASE_HPKE_SUITE, public_key).into())
   }
 
-  pub fn with_browser(&mut self, browser: Option<Browser>) -> &mut Self {
+  pub fn with_browser(&mut self, browser: Option<TlsProfile>) -> &mut Self {
       self.browser = browser;
       self
   }
@@ -55,51 +71,90 @@ impl TlsConfigBuilder {
       self
   }
 
+  /// Offers only HTTP/1.1 in the ALPN extension.
+  pub fn with_http1_only(&mut self) -> &mut Self {
+      self.max_http_version = Version::HTTP_11;
+      self
+  }
+
+  /// Encrypts the ClientHello with the server's ECH configuration (an `ECHConfigList`, e.g. from its `HTTPS` DNS record).
+  pub fn with_ech_config(&mut self, ech_config: Option<Vec<u8>>) -> &mut Self {
+      self.ech_config = ech_config;
+      self
+  }
+
   pub fn with_ignore_tls_errors(&mut self, ignore_tls_errors: bool) -> &mut Self {
       self.ignore_tls_errors = ignore_tls_errors;
       self
   }
 
-  pub fn build(self) -> rustls::ClientConfig {
+  /// Maps the TLS profile to the `rustls` browser emulator.
+  /// 
+  /// The emulator of the pinned `rustls` fork only takes the browser type and version - the signature algorithms,
+  /// key shares and the extension order are the emulator defaults for the browser family and version.
+  /// [`BrowserProfile::validate`](crate::emulation::BrowserProfile::validate) rejects the profiles listing them.
+  fn get_emulator(profile: &TlsProfile) -> RusTLSBrowser {
+    let browser_type = match profile.family {
+      BrowserFamily::Chrome => BrowserType::Chrome,
+      BrowserFamily::Firefox => BrowserType::Firefox,
+      BrowserFamily::Safari => BrowserType::Safari,
+    };
+
+    RusTLSBrowser { browser_type, version: profile.version }
+  }
+
+  /// Builds the `rustls` client configuration.
+  /// 
+  /// Fails if the configuration is inconsistent, e.g. if a custom profile lists a cipher suite or a group the emulator doesn't support.
+  pub fn build(&self) -> Result<rustls::ClientConfig, rustls::Error> {
     let mut root_store = RootCertStore::empty();
     root_store.extend(
         webpki_roots::TLS_SERVER_ROOTS.iter().cloned(),
     );
 
-    let mut config = match self.browser {
-      Some(browser) => {
-        let rustls_browser = match browser {
-          Browser::Chrome => RusTLSBrowser { browser_type: BrowserType::Chrome, version: 125 },
-          Browser::Firefox => RusTLSBrowser { browser_type: BrowserType::Firefox, version: 125 },
-        };
+    let mut config = match &self.browser {
+      Some(profile) => {
+        let rustls_browser = Self::get_emulator(profile);
 
         let mut crypto_provider = CryptoProvider::builder()
             .with_browser_emulator(&rustls_browser)
             .build();
 
-        match browser {
-          Browser::Firefox => {
-            crypto_provider.kx_groups = vec![
-              X25519,
-              SECP256R1,
-              SECP384R1,
-              // TODO : add SECPR521R1
-              &ffdhe::FFDHE2048_KX_GROUP, 
-              &ffdhe::FFDHE3072_KX_GROUP,
-            ];
-          },
-          _ => {}
+        if !profile.cipher_suites.is_empty() {
+          crypto_provider.cipher_suites = profile.cipher_suites.iter()
+            .map(|id| {
+              crypto_provider.cipher_suites.iter()
+                .find(|suite| u16::from(suite.suite()) == *id)
+                .copied()
+                .ok_or_else(|| rustls::Error::General(format!("unsupported cipher suite {:#06x}", id)))
+            })
+            .collect::<Result<Vec<SupportedCipherSuite>, _>>()?;
+        }
+
+        if !profile.supported_groups.is_empty() {
+          crypto_provider.kx_groups = profile.supported_groups.iter()
+            .map(|id| {
+              statics::KX_GROUPS.iter()
+                .find(|group| u16::from(group.name()) == *id)
+                .copied()
+                .ok_or_else(|| rustls::Error::General(format!("unsupported group {:#06x}", id)))
+            })
+            .collect::<Result<Vec<&'static dyn SupportedKxGroup>, _>>()?;
         }
 
         let mut config: rustls::ClientConfig = rustls::ClientConfig::builder_with_provider(
                 crypto_provider.into(),
             )
             // TODO - use the ECH extension consistently
-            .with_ech(self.get_ech_mode()).unwrap()
+            .with_ech(self.get_ech_mode()?)?
             .with_root_certificates(root_store)
             .with_browser_emulator(&rustls_browser)
             .with_no_client_auth();
 
+        if !profile.alpn.is_empty() {
+          config.alpn_protocols = profile.alpn.iter().map(|protocol| protocol.as_bytes().to_vec()).collect();
+        }
+
         if self.ignore_tls_errors {
           config.dangerous().set_certificate_verifier(Arc::new(NoVerifier::new(Some(rustls_browser))));
         }
@@ -114,7 +169,7 @@ impl TlsConfigBuilder {
                 crypto_provider.into(),
             )
             // TODO - use the ECH extension consistently
-            .with_ech(self.get_ech_mode()).unwrap()
+            .with_ech(self.get_ech_mode()?)?
             .with_root_certificates(root_store)
             .with_no_client_auth();
 
@@ -130,6 +185,46 @@ impl TlsConfigBuilder {
       config.alpn_protocols = vec![b"h3".to_vec()];
     };
 
-    config
+    if self.max_http_version == Version::HTTP_11 {
+      config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    };
+
+    Ok(config)
+  }
+
+  /// Renders the ClientHello message sent with the built configuration, without connecting anywhere.
+  /// 
+  /// Fails if the configuration can't be built, see [`TlsConfigBuilder::build`].
+  pub fn client_hello(&self) -> Result<ClientHello, rustls::Error> {
+    let config = Arc::new(self.build()?);
+    let server_name = ServerName::try_from("example.com").unwrap();
+    let mut connection = rustls::ClientConnection::new(config, server_name)?;
+
+    let mut records: Vec<u8> = vec![];
+    connection.write_tls(&mut records)
+      .map_err(|err| rustls::Error::General(format!("couldn't render the ClientHello: {}", err)))?;
+
+    ClientHello::parse(&records)
+      .map_err(|err| rustls::Error::General(format!("couldn't parse the rendered ClientHello: {:?}", err)))
   }
-}
\ No newline at end of file
+}
+
+/// Checks whether `rustls` implements the cipher suite.
+pub(crate) fn is_supported_cipher_suite(id: u16) -> bool {
+  rustls::crypto::aws_lc_rs::ALL_CIPHER_SUITES.iter().any(|suite| u16::from(suite.suite()) == id)
+}
+
+/// Checks whether the named group can be used for the key exchange.
+pub(crate) fn is_supported_group(id: u16) -> bool {
+  statics::KX_GROUPS.iter().any(|group| u16::from(group.name()) == id)
+}
+
+/// Returns the first cipher suite of the list that `rustls` doesn't implement.
+pub(crate) fn unsupported_cipher_suite(cipher_suites: &[u16]) -> Option<u16> {
+  cipher_suites.iter().copied().find(|id| !is_supported_cipher_suite(*id))
+}
+
+/// Returns the first named group of the list that can't be used for the key exchange.
+pub(crate) fn unsupported_group(groups: &[u16]) -> Option<u16> {
+  groups.iter().copied().find(|id| !is_supported_group(*id))
+}
@@ -0,0 +1,244 @@
+//! Minimal pcap / pcapng reader, extracting the TLS ClientHello records from captured TCP traffic.
+
+use std::collections::HashMap;
+
+use super::client_hello::{ClientHello, ClientHelloError};
+
+const PCAP_MAGIC: u32 = 0xa1b2_c3d4;
+const PCAP_MAGIC_NANOSECONDS: u32 = 0xa1b2_3c4d;
+const PCAPNG_SECTION_HEADER: u32 = 0x0a0d_0d0a;
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1a2b_3c4d;
+const PCAPNG_INTERFACE_DESCRIPTION: u32 = 0x0000_0001;
+const PCAPNG_SIMPLE_PACKET: u32 = 0x0000_0003;
+const PCAPNG_ENHANCED_PACKET: u32 = 0x0000_0006;
+
+const LINKTYPE_NULL: u32 = 0;
+const LINKTYPE_ETHERNET: u32 = 1;
+const LINKTYPE_RAW: u32 = 101;
+const LINKTYPE_LINUX_SLL: u32 = 113;
+const LINKTYPE_LINUX_SLL2: u32 = 276;
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86dd;
+const ETHERTYPE_VLAN: u16 = 0x8100;
+
+const IP_PROTOCOL_TCP: u8 = 6;
+
+/// Source and destination address and port of a TCP segment.
+type FlowKey = (Vec<u8>, u16, Vec<u8>, u16);
+
+/// Reads integers in the byte order of the capture file.
+#[derive(Clone, Copy)]
+struct Endianness {
+  little: bool,
+}
+
+impl Endianness {
+  fn u16(&self, bytes: &[u8]) -> Option<u16> {
+    let bytes: [u8; 2] = bytes.get(..2)?.try_into().ok()?;
+    Some(if self.little { u16::from_le_bytes(bytes) } else { u16::from_be_bytes(bytes) })
+  }
+
+  fn u32(&self, bytes: &[u8]) -> Option<u32> {
+    let bytes: [u8; 4] = bytes.get(..4)?.try_into().ok()?;
+    Some(if self.little { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) })
+  }
+}
+
+/// Returns the captured link-layer frames, together with their link type.
+fn frames(capture: &[u8]) -> Result<Vec<(u32, &[u8])>, ClientHelloError> {
+  let magic = capture.get(..4).ok_or(ClientHelloError::UnsupportedCaptureFormat)?;
+  let big_endian_magic = u32::from_be_bytes(magic.try_into().unwrap());
+  let little_endian_magic = u32::from_le_bytes(magic.try_into().unwrap());
+
+  if big_endian_magic == PCAPNG_SECTION_HEADER {
+    return pcapng_frames(capture);
+  }
+
+  let endianness = match (big_endian_magic, little_endian_magic) {
+    (PCAP_MAGIC | PCAP_MAGIC_NANOSECONDS, _) => Endianness { little: false },
+    (_, PCAP_MAGIC | PCAP_MAGIC_NANOSECONDS) => Endianness { little: true },
+    _ => return Err(ClientHelloError::UnsupportedCaptureFormat),
+  };
+
+  // The link type is the last field of the 24-byte file header.
+  let linktype = capture.get(20..24)
+    .and_then(|header| endianness.u32(header))
+    .ok_or(ClientHelloError::Truncated)? & 0x0fff_ffff;
+  let mut frames = vec![];
+  let mut offset = 24;
+
+  while let Some(captured_length) = capture.get(offset + 8..).and_then(|header| endianness.u32(header)) {
+    let start = offset + 16;
+    let end = start + captured_length as usize;
+
+    match capture.get(start..end) {
+      Some(frame) => frames.push((linktype, frame)),
+      None => break,
+    }
+
+    offset = end;
+  }
+
+  Ok(frames)
+}
+
+/// Returns the frames of the packet blocks of a pcapng capture.
+///
+/// A block that doesn't fit in the remaining capture ends the reading (the capture was cut off),
+/// a block too short for its own fields is an error.
+fn pcapng_frames(capture: &[u8]) -> Result<Vec<(u32, &[u8])>, ClientHelloError> {
+  let mut frames = vec![];
+  let mut linktypes: Vec<u32> = vec![];
+  let mut endianness = Endianness { little: true };
+  let mut offset = 0;
+
+  while offset + 12 <= capture.len() {
+    let block = &capture[offset..];
+
+    if u32::from_be_bytes(block[..4].try_into().unwrap()) == PCAPNG_SECTION_HEADER {
+      endianness = Endianness { little: u32::from_le_bytes(block[8..12].try_into().unwrap()) == PCAPNG_BYTE_ORDER_MAGIC };
+      // Interface IDs are scoped to the section.
+      linktypes.clear();
+    }
+
+    let (Some(block_type), Some(block_length)) = (endianness.u32(block), endianness.u32(&block[4..])) else {
+      break;
+    };
+    let block_length = block_length as usize;
+
+    if block_length < 12 {
+      return Err(ClientHelloError::Truncated);
+    }
+
+    if block_length > block.len() {
+      break;
+    }
+
+    let body = &block[8..block_length - 4];
+
+    match block_type {
+      PCAPNG_INTERFACE_DESCRIPTION => {
+        linktypes.push(endianness.u16(body).unwrap_or_default() as u32);
+      },
+      PCAPNG_ENHANCED_PACKET => {
+        // Interface ID, timestamp (2x4 bytes), captured and original length.
+        let (Some(interface), Some(captured_length)) = (endianness.u32(body), body.get(12..).and_then(|fields| endianness.u32(fields))) else {
+          return Err(ClientHelloError::Truncated);
+        };
+        let (interface, captured_length) = (interface as usize, captured_length as usize);
+
+        if let (Some(linktype), Some(frame)) = (linktypes.get(interface), body.get(20..20 + captured_length)) {
+          frames.push((*linktype, frame));
+        }
+      },
+      PCAPNG_SIMPLE_PACKET => {
+        let (Some(original_length), Some(frame)) = (endianness.u32(body), body.get(4..)) else {
+          return Err(ClientHelloError::Truncated);
+        };
+        let original_length = original_length as usize;
+
+        if let Some(linktype) = linktypes.first() {
+          frames.push((*linktype, &frame[..original_length.min(frame.len())]));
+        }
+      },
+      _ => {},
+    }
+
+    offset += block_length;
+  }
+
+  Ok(frames)
+}
+
+/// Strips the link layer, returning the IP packet.
+fn ip_packet(linktype: u32, frame: &[u8]) -> Option<&[u8]> {
+  match linktype {
+    LINKTYPE_ETHERNET => {
+      let mut ethertype = u16::from_be_bytes(frame.get(12..14)?.try_into().ok()?);
+      let mut offset = 14;
+
+      while ethertype == ETHERTYPE_VLAN {
+        ethertype = u16::from_be_bytes(frame.get(offset + 2..offset + 4)?.try_into().ok()?);
+        offset += 4;
+      }
+
+      match ethertype {
+        ETHERTYPE_IPV4 | ETHERTYPE_IPV6 => frame.get(offset..),
+        _ => None,
+      }
+    },
+    LINKTYPE_LINUX_SLL => frame.get(16..),
+    LINKTYPE_LINUX_SLL2 => frame.get(20..),
+    LINKTYPE_NULL => frame.get(4..),
+    LINKTYPE_RAW => Some(frame),
+    _ => None,
+  }
+}
+
+/// Parses the IP and TCP headers, returning the flow and the TCP payload.
+fn tcp_segment(packet: &[u8]) -> Option<(FlowKey, &[u8])> {
+  let (source, destination, segment) = match packet.first()? >> 4 {
+    4 => {
+      let header_length = ((packet[0] & 0x0f) as usize) * 4;
+      let total_length = u16::from_be_bytes(packet.get(2..4)?.try_into().ok()?) as usize;
+
+      if *packet.get(9)? != IP_PROTOCOL_TCP {
+        return None;
+      }
+
+      (packet.get(12..16)?, packet.get(16..20)?, packet.get(header_length..total_length.min(packet.len()))?)
+    },
+    6 => {
+      let payload_length = u16::from_be_bytes(packet.get(4..6)?.try_into().ok()?) as usize;
+
+      // Extension headers are not supported.
+      if *packet.get(6)? != IP_PROTOCOL_TCP {
+        return None;
+      }
+
+      (packet.get(8..24)?, packet.get(24..40)?, packet.get(40..(40 + payload_length).min(packet.len()))?)
+    },
+    _ => return None,
+  };
+
+  let source_port = u16::from_be_bytes(segment.get(0..2)?.try_into().ok()?);
+  let destination_port = u16::from_be_bytes(segment.get(2..4)?.try_into().ok()?);
+  let data_offset = ((segment.get(12)? >> 4) as usize) * 4;
+
+  Some(((source.to_vec(), source_port, destination.to_vec(), destination_port), segment.get(data_offset..)?))
+}
+
+/// Finds the first complete ClientHello in the capture and returns its TLS records.
+///
+/// Only the flows starting with a ClientHello record are tracked. The segments are concatenated in the capture order,
+/// which is enough for ClientHellos spanning multiple segments, as long as there are no retransmissions.
+pub(crate) fn find_client_hello(capture: &[u8]) -> Result<Vec<u8>, ClientHelloError> {
+  let mut flows: HashMap<FlowKey, Vec<u8>> = HashMap::new();
+
+  for (linktype, frame) in frames(capture)? {
+    let Some((flow, payload)) = ip_packet(linktype, frame).and_then(tcp_segment) else {
+      continue;
+    };
+
+    if payload.is_empty() {
+      continue;
+    }
+
+    let buffer = flows.entry(flow).or_default();
+
+    if buffer.is_empty() && !(payload.len() > 5 && payload[0] == 0x16 && payload[5] == 0x01) {
+      continue;
+    }
+
+    buffer.extend_from_slice(payload);
+
+    match ClientHello::parse(buffer) {
+      Ok(_) => return Ok(buffer.clone()),
+      Err(ClientHelloError::Truncated) => continue,
+      Err(_) => buffer.clear(),
+    }
+  }
+
+  Err(ClientHelloError::NoClientHelloFound)
+}
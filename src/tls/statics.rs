@@ -1,3 +1,16 @@
-use rustls::crypto::{aws_lc_rs, hpke::Hpke,};
+use rustls::crypto::aws_lc_rs::kx_group::{SECP256R1, SECP384R1, X25519};
+use rustls::crypto::{aws_lc_rs, hpke::Hpke, SupportedKxGroup};
 
-pub static GREASE_HPKE_SUITE: &dyn Hpke = aws_lc_rs::hpke::DH_KEM_X25519_HKDF_SHA256_AES_128;
\ No newline at end of file
+use super::ffdhe;
+
+pub static GREASE_HPKE_SUITE: &dyn Hpke = aws_lc_rs::hpke::DH_KEM_X25519_HKDF_SHA256_AES_128;
+
+/// Key exchange groups that can be selected by the `supported_groups` list of a TLS profile.
+// TODO : add SECPR521R1
+pub static KX_GROUPS: &[&dyn SupportedKxGroup] = &[
+  X25519,
+  SECP256R1,
+  SECP384R1,
+  &ffdhe::FFDHE2048_KX_GROUP,
+  &ffdhe::FFDHE3072_KX_GROUP,
+];
@@ -0,0 +1,207 @@
+mod common;
+
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use common::TestServer;
+use impit::dns::{DnsAnswer, DnsFuture, HttpsRecord, Resolver};
+use impit::impit::{Impit, TcpFallback};
+use tokio::net::UdpSocket;
+
+/// Resolves every host to `127.0.0.1`, with an `HTTPS` record advertising `h3` while `h3` is set.
+struct H3Resolver {
+  ttl: Duration,
+  h3: AtomicBool,
+  /// The hosts resolved to their IP addresses.
+  resolved: Mutex<Vec<String>>,
+}
+
+impl H3Resolver {
+  fn new(ttl: Duration, h3: bool) -> Arc<H3Resolver> {
+    Arc::new(H3Resolver { ttl, h3: AtomicBool::new(h3), resolved: Mutex::new(vec![]) })
+  }
+}
+
+impl Resolver for H3Resolver {
+  fn lookup_ip<'a>(&'a self, host: &'a str) -> DnsFuture<'a, IpAddr> {
+    Box::pin(async move {
+      self.resolved.lock().unwrap().push(host.to_string());
+      Ok(DnsAnswer { records: vec![IpAddr::V4(Ipv4Addr::LOCALHOST)], ttl: self.ttl })
+    })
+  }
+
+  fn lookup_https<'a>(&'a self, _host: &'a str) -> DnsFuture<'a, HttpsRecord> {
+    Box::pin(async move {
+      let records = match self.h3.load(Ordering::SeqCst) {
+        true => vec![HttpsRecord { priority: 1, alpn: vec![String::from("h3")], ..HttpsRecord::default() }],
+        false => vec![],
+      };
+
+      Ok(DnsAnswer { records, ttl: self.ttl })
+    })
+  }
+}
+
+fn impit(resolver: Arc<H3Resolver>) -> Impit {
+  Impit::builder()
+    .with_http3()
+    .with_resolver(resolver)
+    .with_ignore_tls_errors(true)
+    // There is no HTTP/3 server, the HTTP/3 requests time out.
+    .with_default_timeout(Duration::from_secs(1))
+    .build()
+}
+
+/// The server URLs with a host name, so the resolver is used.
+fn url(server: &TestServer) -> String {
+  server.url().replace("127.0.0.1", "example.test")
+}
+
+fn alt_svc_url(server: &TestServer, alt_svc: &str) -> String {
+  server.headers_url(&[("alt-svc", alt_svc)]).replace("127.0.0.1", "example.test")
+}
+
+/// The TCP port of the server. The HTTP/3 alternatives use the same (UDP) port, nothing listens there.
+fn port(server: &TestServer) -> u16 {
+  url::Url::parse(&server.url()).unwrap().port().unwrap()
+}
+
+#[tokio::test]
+async fn alt_svc_overrides_the_negative_dns_answer() {
+  let server = TestServer::start().await;
+  let impit = impit(H3Resolver::new(Duration::from_secs(60), false));
+
+  let alt_svc = format!("h3=\":{}\"; ma=60", port(&server));
+  let response = impit.get(alt_svc_url(&server, &alt_svc), None).await.unwrap();
+  assert_eq!(response.status(), 200);
+
+  let response = impit.get(url(&server), None).await;
+  assert!(response.is_err());
+  assert_eq!(server.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn alt_svc_expires_after_max_age() {
+  let server = TestServer::start().await;
+  let impit = impit(H3Resolver::new(Duration::from_secs(60), false));
+
+  let alt_svc = format!("h3=\":{}\"; ma=0", port(&server));
+  let response = impit.get(alt_svc_url(&server, &alt_svc), None).await.unwrap();
+  assert_eq!(response.status(), 200);
+
+  let response = impit.get(url(&server), None).await.unwrap();
+  assert_eq!(response.status(), 200);
+}
+
+#[tokio::test]
+async fn other_protocols_are_not_http3() {
+  let server = TestServer::start().await;
+  let impit = impit(H3Resolver::new(Duration::from_secs(60), false));
+
+  let alt_svc = format!("h3-29=\":{0}\", h2=\":{0}\"", port(&server));
+  let response = impit.get(alt_svc_url(&server, &alt_svc), None).await.unwrap();
+  assert_eq!(response.status(), 200);
+
+  let response = impit.get(url(&server), None).await.unwrap();
+  assert_eq!(response.status(), 200);
+}
+
+#[tokio::test]
+async fn http3_requests_go_to_the_advertised_endpoint() {
+  let server = TestServer::start().await;
+  // Stands in for the HTTP/3 server, only receiving the QUIC handshake.
+  let socket = UdpSocket::bind(("127.0.0.1", port(&server))).await.unwrap();
+  let resolver = H3Resolver::new(Duration::from_secs(60), false);
+  let impit = impit(resolver.clone());
+
+  let alt_svc = format!("h3-29=\":443\", h3=\"alt.test:{}\"; ma=60", port(&server));
+  let response = impit.get(alt_svc_url(&server, &alt_svc), None).await.unwrap();
+  assert_eq!(response.status(), 200);
+
+  assert!(impit.get(url(&server), None).await.is_err());
+  assert_eq!(server.requests().len(), 1);
+
+  let mut buffer = [0u8; 2048];
+  let received = tokio::time::timeout(Duration::from_secs(1), socket.recv_from(&mut buffer)).await;
+  assert!(received.is_ok_and(|received| received.is_ok()));
+
+  assert!(resolver.resolved.lock().unwrap().contains(&String::from("alt.test")));
+}
+
+#[tokio::test]
+async fn alternatives_on_other_ports_are_not_used() {
+  let server = TestServer::start().await;
+  // Would receive the QUIC handshake if the alternative port was used.
+  let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+  let impit = impit(H3Resolver::new(Duration::from_secs(60), false));
+
+  let alt_svc = format!("h3=\"alt.test:{}\"; ma=60", socket.local_addr().unwrap().port());
+  impit.get(alt_svc_url(&server, &alt_svc), None).await.unwrap();
+
+  // Sent to the origin over TCP.
+  let response = impit.get(url(&server), None).await.unwrap();
+  assert_eq!(response.status(), 200);
+  assert_eq!(response.url().as_str(), url(&server));
+  assert_eq!(response.extensions().get::<TcpFallback>(), Some(&TcpFallback::EndpointOnOtherPort));
+  assert_eq!(server.requests().len(), 2);
+
+  let mut buffer = [0u8; 2048];
+  let received = tokio::time::timeout(Duration::from_millis(200), socket.recv_from(&mut buffer)).await;
+  assert!(received.is_err());
+}
+
+#[tokio::test]
+async fn alternatives_on_the_origin_port_are_preferred() {
+  let server = TestServer::start().await;
+  let socket = UdpSocket::bind(("127.0.0.1", port(&server))).await.unwrap();
+  let impit = impit(H3Resolver::new(Duration::from_secs(60), false));
+
+  // The same as `h3=":8443", h3=":443"` for a server on port 443.
+  let other_port = UdpSocket::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port();
+  let alt_svc = format!("h3=\":{}\", h3=\":{}\"; ma=60", other_port, port(&server));
+  impit.get(alt_svc_url(&server, &alt_svc), None).await.unwrap();
+
+  // Sent over HTTP/3, which times out.
+  assert!(impit.get(url(&server), None).await.is_err());
+  assert_eq!(server.requests().len(), 1);
+
+  let mut buffer = [0u8; 2048];
+  let received = tokio::time::timeout(Duration::from_secs(1), socket.recv_from(&mut buffer)).await;
+  assert!(received.is_ok_and(|received| received.is_ok()));
+}
+
+#[tokio::test]
+async fn dns_answers_expire_with_their_ttl() {
+  for (ttl, h3_after_change) in [(0, false), (60, true)] {
+    let server = TestServer::start().await;
+    let resolver = H3Resolver::new(Duration::from_secs(ttl), true);
+    let impit = impit(resolver.clone());
+
+    assert!(impit.get(url(&server), None).await.is_err(), "ttl {}", ttl);
+
+    resolver.h3.store(false, Ordering::SeqCst);
+    let response = impit.get(url(&server), None).await;
+
+    assert_eq!(response.is_err(), h3_after_change, "ttl {}", ttl);
+  }
+}
+
+#[tokio::test]
+async fn origins_of_one_host_with_different_alternatives_use_tcp() {
+  let first = TestServer::start().await;
+  let second = TestServer::start().await;
+  let impit = impit(H3Resolver::new(Duration::from_secs(60), false));
+
+  impit.get(alt_svc_url(&first, &format!("h3=\"first.test:{}\"; ma=60", port(&first))), None).await.unwrap();
+  impit.get(alt_svc_url(&second, &format!("h3=\"second.test:{}\"; ma=60", port(&second))), None).await.unwrap();
+
+  // The resolver only gets the host name, so it can't tell the connections to the two origins apart.
+  for server in [&first, &second] {
+    let response = impit.get(url(server), None).await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.extensions().get::<TcpFallback>(), Some(&TcpFallback::AmbiguousEndpoint));
+    assert_eq!(server.requests().len(), 2);
+  }
+}
@@ -0,0 +1,148 @@
+mod common;
+
+use common::{RecordedRequest, TestServer};
+use impit::emulation::{Browser, BrowserFamily, ChromeVersion, ClientHello, FirefoxVersion, SafariVersion, TlsFingerprint};
+use impit::impit::Impit;
+
+async fn request(server: &TestServer, browser: Browser) -> RecordedRequest {
+  let impit = Impit::builder()
+    .with_browser(browser)
+    .with_ignore_tls_errors(true)
+    .build();
+
+  let response = impit.get(server.url(), None).await.unwrap();
+  assert_eq!(response.status(), 200);
+
+  server.requests().pop().unwrap()
+}
+
+fn without_grease(values: &[u16]) -> Vec<u16> {
+  values.iter().copied().filter(|value| value & 0x0f0f != 0x0a0a).collect()
+}
+
+fn assert_client_hello_matches_profile(client_hello: &ClientHello, browser: Browser) {
+  let profile = browser.profile();
+
+  if !profile.tls.cipher_suites.is_empty() {
+    assert_eq!(without_grease(&client_hello.cipher_suites), profile.tls.cipher_suites, "{:?}", browser);
+  }
+
+  if !profile.tls.supported_groups.is_empty() {
+    assert_eq!(without_grease(&client_hello.supported_groups), profile.tls.supported_groups, "{:?}", browser);
+  }
+
+  assert_eq!(client_hello.alpn, profile.tls.alpn, "{:?}", browser);
+}
+
+#[tokio::test]
+async fn safari_profiles() {
+  let server = TestServer::start().await;
+
+  for (browser, platform) in [
+    (Browser::Safari(SafariVersion::V17), "Macintosh"),
+    (Browser::Safari(SafariVersion::V18), "Macintosh"),
+    (Browser::SafariIos(SafariVersion::V17), "iPhone"),
+    (Browser::SafariIos(SafariVersion::V18), "iPhone"),
+  ] {
+    let profile = browser.profile();
+    assert_eq!(profile.tls.family, BrowserFamily::Safari);
+    assert!(profile.client_hints.is_empty());
+
+    let request = request(&server, browser).await;
+    let user_agent = request.header("user-agent").unwrap();
+
+    assert!(user_agent.contains(platform) && user_agent.contains("Version/1"), "{}", user_agent);
+    assert!(!user_agent.contains("Chrome"), "{}", user_agent);
+
+    // Safari doesn't send the client hints nor `upgrade-insecure-requests`.
+    assert!(request.headers.iter().all(|(name, _)| !name.starts_with("sec-ch-")), "{:?}", request.headers);
+    assert_eq!(request.header("upgrade-insecure-requests"), None);
+
+    assert_client_hello_matches_profile(&request.connection.client_hello, browser);
+
+    // The JA4 of Safari 17 and 18 (macOS and iOS). The test server is an IP address, so the SNI extension isn't sent.
+    let fingerprint = TlsFingerprint::from_client_hello(&request.connection.client_hello);
+    assert_eq!(fingerprint.ja4, "t13i2013h2_a09f3c656075_14788d8d241b", "{:?}", browser);
+  }
+}
+
+#[tokio::test]
+async fn edge_profiles() {
+  let server = TestServer::start().await;
+
+  for (version, major) in [(ChromeVersion::V125, "125"), (ChromeVersion::V131, "131")] {
+    let browser = Browser::Edge(version);
+    let profile = browser.profile();
+
+    // Edge shares the TLS stack and the HTTP/2 implementation with Chrome.
+    let chrome = Browser::Chrome(version).profile();
+    assert_eq!(profile.tls, chrome.tls);
+    assert_eq!(profile.http2, chrome.http2);
+
+    let request = request(&server, browser).await;
+
+    let user_agent = request.header("user-agent").unwrap();
+    assert!(user_agent.contains("Windows NT 10.0") && user_agent.contains(&format!("Edg/{}", major)), "{}", user_agent);
+
+    let brands = request.header("sec-ch-ua").unwrap();
+    assert!(brands.contains(&format!("\"Microsoft Edge\";v=\"{}\"", major)), "{}", brands);
+    assert_eq!(request.header("sec-ch-ua-platform"), Some("\"Windows\""));
+
+    assert_client_hello_matches_profile(&request.connection.client_hello, browser);
+  }
+}
+
+#[tokio::test]
+async fn chrome_releases_are_pinned() {
+  let server = TestServer::start().await;
+
+  for (version, major) in [(ChromeVersion::V125, 125), (ChromeVersion::V131, 131)] {
+    let browser = Browser::Chrome(version);
+    let profile = browser.profile();
+
+    assert_eq!(profile.name, format!("Chrome {}", major));
+    assert_eq!(profile.tls.family, BrowserFamily::Chrome);
+    assert_eq!(profile.tls.version, major);
+
+    let request = request(&server, browser).await;
+
+    let user_agent = request.header("user-agent").unwrap();
+    assert!(user_agent.contains(&format!("Chrome/{}.0.0.0", major)), "{}", user_agent);
+
+    let brands = request.header("sec-ch-ua").unwrap();
+    assert!(brands.contains(&format!("\"Google Chrome\";v=\"{}\"", major)), "{}", brands);
+
+    assert_client_hello_matches_profile(&request.connection.client_hello, browser);
+  }
+
+  assert_eq!(Browser::Chrome(ChromeVersion::default()), Browser::Chrome(ChromeVersion::V131));
+}
+
+#[tokio::test]
+async fn firefox_releases_are_pinned() {
+  let server = TestServer::start().await;
+
+  for (version, major) in [(FirefoxVersion::V128, 128), (FirefoxVersion::V133, 133)] {
+    let browser = Browser::Firefox(version);
+    let profile = browser.profile();
+
+    assert_eq!(profile.name, format!("Firefox {}", major));
+    assert_eq!(profile.tls.family, BrowserFamily::Firefox);
+    assert_eq!(profile.tls.version, major);
+    assert!(profile.client_hints.is_empty());
+
+    let request = request(&server, browser).await;
+
+    let user_agent = request.header("user-agent").unwrap();
+    assert!(user_agent.contains(&format!("rv:{}.0) Gecko/20100101 Firefox/{}.0", major, major)), "{}", user_agent);
+    assert!(request.headers.iter().all(|(name, _)| !name.starts_with("sec-ch-")), "{:?}", request.headers);
+
+    // Firefox offers the finite field groups as well.
+    let groups = without_grease(&request.connection.client_hello.supported_groups);
+    assert_eq!(groups, [0x001d, 0x0017, 0x0018, 0x0100, 0x0101]);
+
+    assert_client_hello_matches_profile(&request.connection.client_hello, browser);
+  }
+
+  assert_eq!(Browser::Firefox(FirefoxVersion::default()), Browser::Firefox(FirefoxVersion::V133));
+}
@@ -0,0 +1,280 @@
+//! Parses a ClientHello captured from `curl` 7.88.1 (OpenSSL 3.0), sent to `https://example.com:8443/` with `--http2`.
+//!
+//! The pcap tests wrap the captured record in Ethernet / IPv4 / TCP frames.
+use std::path::PathBuf;
+
+use impit::emulation::{Browser, BrowserFamily, ChromeVersion, ClientHello, ClientHelloError};
+use impit::impit::Impit;
+
+const CLIENT_HELLO: &[u8] = include_bytes!("fixtures/curl_client_hello.bin");
+
+fn assert_curl_client_hello(client_hello: &ClientHello) {
+  assert_eq!(client_hello.legacy_version, 0x0303);
+  assert_eq!(client_hello.cipher_suites.len(), 31);
+  assert_eq!(client_hello.cipher_suites[..4], [0x1302, 0x1303, 0x1301, 0xc02c]);
+  assert_eq!(client_hello.cipher_suites.last(), Some(&0x00ff));
+  assert_eq!(client_hello.extensions, [0x0000, 0x000b, 0x000a, 0x0010, 0x0016, 0x0017, 0x0031, 0x000d, 0x002b, 0x002d, 0x0033, 0x0015]);
+  assert_eq!(client_hello.server_name.as_deref(), Some("example.com"));
+  assert_eq!(client_hello.supported_groups, [0x001d, 0x0017, 0x001e, 0x0019, 0x0018, 0x0100, 0x0101, 0x0102, 0x0103, 0x0104]);
+  assert_eq!(client_hello.ec_point_formats, [0, 1, 2]);
+  assert_eq!(client_hello.signature_algorithms.len(), 20);
+  assert_eq!(client_hello.signature_algorithms[..3], [0x0403, 0x0503, 0x0603]);
+  assert_eq!(client_hello.alpn, ["h2", "http/1.1"]);
+  assert_eq!(client_hello.supported_versions, [0x0304, 0x0303, 0x0302, 0x0301]);
+  assert_eq!(client_hello.key_shares, [0x001d]);
+}
+
+#[test]
+fn captured_profiles_can_be_used() {
+  let client_hello = ClientHello::parse(CLIENT_HELLO).unwrap();
+  let tls = client_hello.to_tls_profile(BrowserFamily::Chrome, 131);
+
+  // The CBC and DHE suites, the renegotiation SCSV, x448, secp521r1 and the larger finite field groups are left out.
+  assert_eq!(tls.cipher_suites, [0x1302, 0x1303, 0x1301, 0xc02c, 0xc030, 0xcca9, 0xcca8, 0xc02b, 0xc02f]);
+  assert_eq!(tls.supported_groups, [0x001d, 0x0017, 0x0018, 0x0100, 0x0101]);
+
+  let (cipher_suites, groups) = client_hello.unsupported_values();
+  assert_eq!(cipher_suites.len(), 22);
+  assert!(cipher_suites.contains(&0x00ff));
+  assert_eq!(groups, [0x001e, 0x0019, 0x0102, 0x0103, 0x0104]);
+
+  let mut profile = Browser::Chrome(ChromeVersion::V131).profile();
+  profile.tls = tls;
+
+  assert!(profile.validate().is_ok());
+  assert!(Impit::builder().with_browser_profile(profile).try_build().is_ok());
+}
+
+/// Splits the handshake message of the captured record into TLS records of at most `fragment_length` bytes.
+fn fragmented(fragment_length: usize) -> Vec<u8> {
+  CLIENT_HELLO[5..].chunks(fragment_length)
+    .flat_map(|fragment| {
+      let mut record = vec![0x16, 0x03, 0x01];
+      record.extend_from_slice(&(fragment.len() as u16).to_be_bytes());
+      record.extend_from_slice(fragment);
+      record
+    })
+    .collect()
+}
+
+#[test]
+fn captured_client_hello_is_parsed() {
+  assert_curl_client_hello(&ClientHello::parse(CLIENT_HELLO).unwrap());
+  // Just the handshake message, without the record header.
+  assert_curl_client_hello(&ClientHello::parse(&CLIENT_HELLO[5..]).unwrap());
+  // The handshake message fragmented over multiple records.
+  assert_curl_client_hello(&ClientHello::parse(&fragmented(100)).unwrap());
+}
+
+#[test]
+fn truncated_client_hellos_are_rejected() {
+  for length in 0..CLIENT_HELLO.len() {
+    let result = ClientHello::parse(&CLIENT_HELLO[..length]);
+    assert!(matches!(result, Err(ClientHelloError::Truncated)), "{} bytes: {:?}", length, result);
+  }
+
+  let records = fragmented(100);
+  let result = ClientHello::parse(&records[..records.len() - 1]);
+  assert!(matches!(result, Err(ClientHelloError::Truncated)), "{:?}", result);
+}
+
+#[test]
+fn malformed_client_hellos_are_rejected() {
+  // A ServerHello handshake message.
+  let mut server_hello = CLIENT_HELLO.to_vec();
+  server_hello[5] = 0x02;
+  assert!(matches!(ClientHello::parse(&server_hello), Err(ClientHelloError::NotAClientHello)));
+
+  // The second fragment of the handshake is an application data record.
+  let mut records = fragmented(100);
+  records[105] = 0x17;
+  assert!(matches!(ClientHello::parse(&records), Err(ClientHelloError::NotAClientHello)));
+
+  // The length of the extensions block points past the end of the message.
+  let mut client_hello = CLIENT_HELLO.to_vec();
+  let extensions_length = 5 + 4 + 2 + 32 + 1 + 32 + 2 + 62 + 2;
+  client_hello[extensions_length..extensions_length + 2].copy_from_slice(&[0xff, 0xff]);
+  assert!(matches!(ClientHello::parse(&client_hello), Err(ClientHelloError::Truncated)));
+
+  // The length of the server name points past the end of the extension.
+  let mut client_hello = CLIENT_HELLO.to_vec();
+  let server_name_list_length = extensions_length + 2 + 4;
+  client_hello[server_name_list_length + 1] = 0x20;
+  assert!(matches!(ClientHello::parse(&client_hello), Err(ClientHelloError::Truncated)));
+}
+
+/// Wraps the TCP payload in an Ethernet frame with IPv4 and TCP headers.
+fn ethernet_frame(payload: &[u8], sequence_number: u32) -> Vec<u8> {
+  let mut frame = vec![0; 12];
+  frame.extend_from_slice(&[0x08, 0x00]);
+
+  // IPv4 header without options
+  frame.extend_from_slice(&[0x45, 0x00]);
+  frame.extend_from_slice(&((20 + 20 + payload.len()) as u16).to_be_bytes());
+  frame.extend_from_slice(&[0, 0, 0x40, 0, 64, 6, 0, 0]);
+  frame.extend_from_slice(&[10, 0, 0, 2, 93, 184, 215, 14]);
+
+  // TCP header without options
+  frame.extend_from_slice(&51234u16.to_be_bytes());
+  frame.extend_from_slice(&443u16.to_be_bytes());
+  frame.extend_from_slice(&sequence_number.to_be_bytes());
+  frame.extend_from_slice(&[0, 0, 0, 0, 0x50, 0x18, 0xff, 0xff, 0, 0, 0, 0]);
+
+  frame.extend_from_slice(payload);
+  frame
+}
+
+/// The captured ClientHello, sent in two TCP segments.
+fn frames() -> Vec<Vec<u8>> {
+  let (first, second) = CLIENT_HELLO.split_at(300);
+  vec![ethernet_frame(first, 1), ethernet_frame(second, 301)]
+}
+
+fn pcap(frames: &[Vec<u8>], little_endian: bool) -> Vec<u8> {
+  let u32_bytes = |value: u32| if little_endian { value.to_le_bytes() } else { value.to_be_bytes() };
+  let u16_bytes = |value: u16| if little_endian { value.to_le_bytes() } else { value.to_be_bytes() };
+
+  let mut capture = vec![];
+  capture.extend_from_slice(&u32_bytes(0xa1b2_c3d4));
+  capture.extend_from_slice(&u16_bytes(2));
+  capture.extend_from_slice(&u16_bytes(4));
+  capture.extend_from_slice(&[0; 8]);
+  capture.extend_from_slice(&u32_bytes(65535));
+  // LINKTYPE_ETHERNET
+  capture.extend_from_slice(&u32_bytes(1));
+
+  for frame in frames {
+    capture.extend_from_slice(&[0; 8]);
+    capture.extend_from_slice(&u32_bytes(frame.len() as u32));
+    capture.extend_from_slice(&u32_bytes(frame.len() as u32));
+    capture.extend_from_slice(frame);
+  }
+
+  capture
+}
+
+fn pcapng_block(block_type: u32, body: &[u8]) -> Vec<u8> {
+  let mut body = body.to_vec();
+  body.resize(body.len().div_ceil(4) * 4, 0);
+
+  let length = (12 + body.len()) as u32;
+  let mut block = vec![];
+  block.extend_from_slice(&block_type.to_le_bytes());
+  block.extend_from_slice(&length.to_le_bytes());
+  block.extend_from_slice(&body);
+  block.extend_from_slice(&length.to_le_bytes());
+  block
+}
+
+fn pcapng_header() -> Vec<u8> {
+  let mut section_header = vec![];
+  section_header.extend_from_slice(&0x1a2b_3c4du32.to_le_bytes());
+  section_header.extend_from_slice(&1u16.to_le_bytes());
+  section_header.extend_from_slice(&0u16.to_le_bytes());
+  section_header.extend_from_slice(&u64::MAX.to_le_bytes());
+
+  // LINKTYPE_ETHERNET, snap length
+  let interface_description = [1, 0, 0, 0, 0xff, 0xff, 0, 0];
+
+  [pcapng_block(0x0a0d_0d0a, &section_header), pcapng_block(1, &interface_description)].concat()
+}
+
+fn enhanced_packet(frame: &[u8]) -> Vec<u8> {
+  let mut body = vec![0; 12];
+  body.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+  body.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+  body.extend_from_slice(frame);
+  pcapng_block(6, &body)
+}
+
+fn simple_packet(frame: &[u8]) -> Vec<u8> {
+  let mut body = (frame.len() as u32).to_le_bytes().to_vec();
+  body.extend_from_slice(frame);
+  pcapng_block(3, &body)
+}
+
+fn pcapng(frames: &[Vec<u8>], packet: fn(&[u8]) -> Vec<u8>) -> Vec<u8> {
+  let mut capture = pcapng_header();
+  for frame in frames {
+    capture.extend(packet(frame));
+  }
+  capture
+}
+
+fn read_capture(name: &str, capture: &[u8]) -> Result<ClientHello, ClientHelloError> {
+  let path: PathBuf = std::env::temp_dir().join(format!("impit-{}-{}.pcap", name, std::process::id()));
+  std::fs::write(&path, capture).unwrap();
+
+  let result = ClientHello::from_pcap(&path);
+  std::fs::remove_file(path).unwrap();
+  result
+}
+
+#[test]
+fn client_hello_is_read_from_captures() {
+  let captures = [
+    ("little-endian", pcap(&frames(), true)),
+    ("big-endian", pcap(&frames(), false)),
+    ("enhanced-packets", pcapng(&frames(), enhanced_packet)),
+    ("simple-packets", pcapng(&frames(), simple_packet)),
+  ];
+
+  for (name, capture) in captures {
+    let client_hello = read_capture(name, &capture).unwrap_or_else(|error| panic!("{}: {:?}", name, error));
+    assert_curl_client_hello(&client_hello);
+  }
+}
+
+#[test]
+fn truncated_captures_are_rejected() {
+  let capture = pcap(&frames(), true);
+
+  // The file header ends before the link type.
+  for length in [4, 19, 23] {
+    let result = read_capture("short-header", &capture[..length]);
+    assert!(matches!(result, Err(ClientHelloError::Truncated)), "{} bytes: {:?}", length, result);
+  }
+
+  // The capture ends in the middle of the second segment.
+  let result = read_capture("short-packet", &capture[..capture.len() - 10]);
+  assert!(matches!(result, Err(ClientHelloError::NoClientHelloFound)), "{:?}", result);
+
+  // An enhanced packet block too short for the captured length field.
+  let mut capture = pcapng_header();
+  capture.extend(pcapng_block(6, &[0; 12]));
+  let result = read_capture("short-enhanced-packet", &capture);
+  assert!(matches!(result, Err(ClientHelloError::Truncated)), "{:?}", result);
+
+  // A simple packet block without the original length field.
+  let mut capture = pcapng_header();
+  capture.extend(pcapng_block(3, &[]));
+  let result = read_capture("short-simple-packet", &capture);
+  assert!(matches!(result, Err(ClientHelloError::Truncated)), "{:?}", result);
+
+  // A block length shorter than the block header.
+  let mut capture = pcapng_header();
+  capture.extend_from_slice(&[6, 0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0]);
+  let result = read_capture("short-block", &capture);
+  assert!(matches!(result, Err(ClientHelloError::Truncated)), "{:?}", result);
+}
+
+#[test]
+fn malformed_captures_are_rejected() {
+  let result = read_capture("no-magic", b"GET / HTTP/1.1\r\n\r\n");
+  assert!(matches!(result, Err(ClientHelloError::UnsupportedCaptureFormat)), "{:?}", result);
+
+  let result = read_capture("empty", &[]);
+  assert!(matches!(result, Err(ClientHelloError::UnsupportedCaptureFormat)), "{:?}", result);
+
+  // Valid frames without any TLS traffic.
+  let result = read_capture("no-client-hello", &pcap(&[ethernet_frame(b"GET / HTTP/1.1\r\n\r\n", 1)], true));
+  assert!(matches!(result, Err(ClientHelloError::NoClientHelloFound)), "{:?}", result);
+
+  // Frames too short for their IP and TCP headers are skipped.
+  let mut frames = frames();
+  frames.insert(0, frames[0][..30].to_vec());
+  assert_curl_client_hello(&read_capture("short-frame", &pcap(&frames, true)).unwrap());
+
+  let result = ClientHello::from_pcap(std::env::temp_dir().join("impit-missing-capture.pcap"));
+  assert!(matches!(result, Err(ClientHelloError::IoError(_))), "{:?}", result);
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use common::TestServer;
+use impit::emulation::{Browser, ChromeVersion, FirefoxVersion};
+use impit::impit::Impit;
+
+#[tokio::test]
+async fn accept_ch_enables_the_high_entropy_hints() {
+  let server = TestServer::start().await;
+
+  let impit = Impit::builder()
+    .with_browser(Browser::Chrome(ChromeVersion::V131))
+    .with_ignore_tls_errors(true)
+    .build();
+
+  impit.get(server.headers_url(&[("Accept-CH", "Sec-CH-UA-Arch, Sec-CH-UA-Full-Version-List")]), None).await.unwrap();
+  impit.get(server.url(), None).await.unwrap();
+
+  let requests = server.requests();
+
+  assert_eq!(requests[0].header("sec-ch-ua-arch"), None);
+  assert_eq!(requests[1].header("sec-ch-ua-arch"), Some("\"x86\""));
+  assert_eq!(
+    requests[1].header("sec-ch-ua-full-version-list"),
+    Some("\"Google Chrome\";v=\"131.0.6778.85\", \"Chromium\";v=\"131.0.6778.85\", \"Not_A Brand\";v=\"24.0.0.0\""),
+  );
+  assert_eq!(requests[1].header("sec-ch-ua-model"), None);
+
+  let names: Vec<&str> = requests[1].headers.iter().map(|(name, _)| name.as_str()).collect();
+  assert_eq!(&names[..5], ["sec-ch-ua", "sec-ch-ua-mobile", "sec-ch-ua-platform", "sec-ch-ua-arch", "sec-ch-ua-full-version-list"]);
+}
+
+#[tokio::test]
+async fn critical_ch_retries_the_navigation() {
+  let server = TestServer::start().await;
+
+  let impit = Impit::builder()
+    .with_browser(Browser::Edge(ChromeVersion::V131))
+    .with_ignore_tls_errors(true)
+    .build();
+
+  let url = server.headers_url(&[("Accept-CH", "Sec-CH-UA-Platform-Version"), ("Critical-CH", "Sec-CH-UA-Platform-Version")]);
+  impit.get(&url, None).await.unwrap();
+
+  let requests = server.requests();
+
+  assert_eq!(requests.len(), 2);
+  assert_eq!(requests[0].header("sec-ch-ua-platform-version"), None);
+  assert_eq!(requests[1].header("sec-ch-ua-platform-version"), Some("\"15.0.0\""));
+
+  // The hints are stored now, so the next request doesn't need a retry.
+  impit.get(&url, None).await.unwrap();
+  assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn browsers_without_client_hints_ignore_accept_ch() {
+  let server = TestServer::start().await;
+
+  let impit = Impit::builder()
+    .with_browser(Browser::Firefox(FirefoxVersion::V133))
+    .with_ignore_tls_errors(true)
+    .build();
+
+  impit.get(server.headers_url(&[("Accept-CH", "Sec-CH-UA-Arch"), ("Critical-CH", "Sec-CH-UA-Arch")]), None).await.unwrap();
+  impit.get(server.url(), None).await.unwrap();
+
+  let requests = server.requests();
+
+  assert_eq!(requests.len(), 2);
+  assert!(requests.iter().all(|request| request.header("sec-ch-ua-arch").is_none()));
+}
+
+#[tokio::test]
+async fn chrome_125_quotes_the_platform_hint() {
+  let server = TestServer::start().await;
+
+  let impit = Impit::builder()
+    .with_browser(Browser::Chrome(ChromeVersion::V125))
+    .with_ignore_tls_errors(true)
+    .build();
+
+  impit.get(server.url(), None).await.unwrap();
+
+  assert_eq!(server.requests()[0].header("sec-ch-ua-platform"), Some("\"Linux\""));
+}
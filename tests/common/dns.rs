@@ -0,0 +1,87 @@
+//! A local DNS stand-in, answering the `HTTPS` record queries over UDP. The other queries get empty answers.
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use hickory_proto::op::{Message, MessageType};
+use hickory_proto::rr::rdata::svcb::{Alpn, SvcParamKey, SvcParamValue, SVCB};
+use hickory_proto::rr::rdata::HTTPS;
+use hickory_proto::rr::{Name, RData, Record, RecordType};
+use tokio::net::UdpSocket;
+
+/// Handle to a running DNS server. The server runs until the test runtime shuts down.
+pub struct TestDnsServer {
+  address: SocketAddr,
+  queries: Arc<Mutex<Vec<(String, RecordType)>>>,
+}
+
+impl TestDnsServer {
+  /// Starts the server on a random local port. The `h3_hosts` get an `HTTPS` record advertising the `h3` ALPN.
+  pub async fn start(h3_hosts: &[&str]) -> TestDnsServer {
+    let records = h3_hosts.iter()
+      .map(|host| {
+        let alpn = SvcParamValue::Alpn(Alpn(vec![String::from("h3"), String::from("h2")]));
+        (host.to_string(), SVCB::new(1, Name::root(), vec![(SvcParamKey::Alpn, alpn)]))
+      })
+      .collect();
+
+    TestDnsServer::start_with_records(records).await
+  }
+
+  /// Starts the server on a random local port, answering the `HTTPS` queries with the given records of the (lowercase) hosts.
+  pub async fn start_with_records(records: Vec<(String, SVCB)>) -> TestDnsServer {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let address = socket.local_addr().unwrap();
+    let queries: Arc<Mutex<Vec<(String, RecordType)>>> = Arc::new(Mutex::new(vec![]));
+
+    let recorded = queries.clone();
+    tokio::spawn(async move {
+      let mut buffer = [0u8; 4096];
+
+      loop {
+        let (length, peer) = match socket.recv_from(&mut buffer).await {
+          Ok(received) => received,
+          Err(_) => return,
+        };
+
+        let Ok(request) = Message::from_vec(&buffer[..length]) else {
+          continue;
+        };
+
+        let mut response = Message::new();
+        response
+          .set_id(request.id())
+          .set_message_type(MessageType::Response)
+          .set_recursion_desired(request.recursion_desired())
+          .set_recursion_available(true);
+
+        for query in request.queries() {
+          let host = query.name().to_ascii().trim_end_matches('.').to_ascii_lowercase();
+          recorded.lock().unwrap().push((host.clone(), query.query_type()));
+
+          if query.query_type() == RecordType::HTTPS {
+            for (_, record) in records.iter().filter(|(record_host, _)| *record_host == host) {
+              response.add_answer(Record::from_rdata(query.name().clone(), 300, RData::HTTPS(HTTPS(record.clone()))));
+            }
+          }
+
+          response.add_query(query.clone());
+        }
+
+        let _ = socket.send_to(&response.to_vec().unwrap(), peer).await;
+      }
+    });
+
+    TestDnsServer { address, queries }
+  }
+
+  /// The address to pass to [`NameServer::udp`](impit::dns::NameServer::udp).
+  pub fn address(&self) -> SocketAddr {
+    self.address
+  }
+
+  /// Returns the (lowercase) names and record types of all the queries received so far.
+  pub fn queries(&self) -> Vec<(String, RecordType)> {
+    self.queries.lock().unwrap().clone()
+  }
+}
@@ -0,0 +1,491 @@
+//! A minimal local HTTPS server used by the integration tests.
+//!
+//! The server terminates TLS with a self-signed certificate (so the clients have to be built with `with_ignore_tls_errors(true)`)
+//! and speaks just enough HTTP/2 and HTTP/1.1 to answer the requests with an empty `200 OK` response.
+//! Requests to `/redirect/<status>?to=<url>` are answered with the given redirect status instead,
+//! requests to `/headers?<name>=<value>` get the query parameters as the response headers.
+//! Every request is recorded together with the fingerprint of its connection - the ClientHello, the HTTP/2 `SETTINGS`,
+//! `WINDOW_UPDATE` and `PRIORITY` frames and the order of the (pseudo-)headers, which is otherwise hidden by the HTTP/2 libraries.
+#![allow(dead_code)]
+
+pub mod dns;
+pub mod quic;
+
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use impit::emulation::{ClientHello, ClientHelloError};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::TlsAcceptor;
+
+const FRAME_HEADERS: u8 = 0x1;
+const FRAME_PRIORITY: u8 = 0x2;
+const FRAME_SETTINGS: u8 = 0x4;
+const FRAME_PING: u8 = 0x6;
+const FRAME_GOAWAY: u8 = 0x7;
+const FRAME_WINDOW_UPDATE: u8 = 0x8;
+const FRAME_CONTINUATION: u8 = 0x9;
+
+const FLAG_ACK: u8 = 0x1;
+const FLAG_END_STREAM: u8 = 0x1;
+const FLAG_END_HEADERS: u8 = 0x4;
+const FLAG_PADDED: u8 = 0x8;
+const FLAG_PRIORITY: u8 = 0x20;
+
+const PREFACE_LENGTH: usize = 24;
+
+/// Stream priority, as sent in the `PRIORITY` frames or the `HEADERS` frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Priority {
+  pub stream_dependency: u32,
+  pub exclusive: bool,
+  pub weight: u16,
+}
+
+impl Priority {
+  fn parse(bytes: &[u8]) -> Priority {
+    let dependency = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+
+    Priority {
+      stream_dependency: dependency & 0x7FFF_FFFF,
+      exclusive: dependency & 0x8000_0000 != 0,
+      weight: bytes[4] as u16 + 1,
+    }
+  }
+}
+
+/// Connection-level data sent by the client before the requests.
+#[derive(Debug, Clone)]
+pub struct RecordedConnection {
+  pub client_hello: ClientHello,
+  /// The first `SETTINGS` frame sent by the client, in the wire order.
+  pub settings: Vec<(u16, u32)>,
+  /// The increment of the first connection-level `WINDOW_UPDATE` frame.
+  pub window_update_increment: Option<u32>,
+  /// `PRIORITY` frames sent by the client, with the stream ID they were sent for.
+  pub priority_frames: Vec<(u32, Priority)>,
+}
+
+/// A single request, as seen by the server.
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+  /// Names of the pseudo-headers in the order they were sent.
+  pub pseudo_headers: Vec<String>,
+  /// Value of the `:method` pseudo-header.
+  pub method: String,
+  /// Value of the `:path` pseudo-header.
+  pub path: String,
+  /// Regular headers in the order they were sent. Over HTTP/1.1, the names keep their original letter case.
+  pub headers: Vec<(String, String)>,
+  /// Whether the request was sent over HTTP/1.1.
+  pub http1: bool,
+  /// Priority fields of the `HEADERS` frame, if present.
+  pub priority: Option<Priority>,
+  /// The connection the request was sent over, at the time of the request.
+  pub connection: RecordedConnection,
+}
+
+impl RecordedRequest {
+  /// Returns the value of the first header with the given (case-insensitive) name.
+  pub fn header(&self, name: &str) -> Option<&str> {
+    self.headers.iter()
+      .find(|(header_name, _)| header_name.eq_ignore_ascii_case(name))
+      .map(|(_, value)| value.as_str())
+  }
+
+  /// Renders the HTTP/2 fingerprint in the Akamai format, i.e. `SETTINGS|WINDOW_UPDATE|PRIORITY|pseudo-header order`.
+  pub fn akamai_fingerprint(&self) -> String {
+    let settings = self.connection.settings.iter()
+      .map(|(id, value)| format!("{}:{}", id, value))
+      .collect::<Vec<_>>()
+      .join(";");
+
+    let window_update = self.connection.window_update_increment
+      .map(|increment| increment.to_string())
+      .unwrap_or(String::from("00"));
+
+    let priority_frames = if self.connection.priority_frames.is_empty() {
+      String::from("0")
+    } else {
+      self.connection.priority_frames.iter()
+        .map(|(stream_id, priority)| format!("{}:{}:{}:{}", stream_id, priority.exclusive as u8, priority.stream_dependency, priority.weight))
+        .collect::<Vec<_>>()
+        .join(",")
+    };
+
+    let pseudo_headers = self.pseudo_headers.iter()
+      .map(|name| name.trim_start_matches(':').chars().next().unwrap_or_default().to_string())
+      .collect::<Vec<_>>()
+      .join(",");
+
+    format!("{}|{}|{}|{}", settings, window_update, priority_frames, pseudo_headers)
+  }
+}
+
+/// Handle to a running test server. The server runs until the test runtime shuts down.
+pub struct TestServer {
+  port: u16,
+  requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl TestServer {
+  /// Starts the server on a random local port.
+  pub async fn start() -> TestServer {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let acceptor = TlsAcceptor::from(Arc::new(server_tls_config()));
+    let requests: Arc<Mutex<Vec<RecordedRequest>>> = Arc::new(Mutex::new(vec![]));
+
+    let recorded = requests.clone();
+    tokio::spawn(async move {
+      loop {
+        let (stream, _) = match listener.accept().await {
+          Ok(connection) => connection,
+          Err(_) => return,
+        };
+
+        let acceptor = acceptor.clone();
+        let recorded = recorded.clone();
+
+        tokio::spawn(async move {
+          let _ = serve_connection(stream, acceptor, recorded).await;
+        });
+      }
+    });
+
+    TestServer { port, requests }
+  }
+
+  /// URL pointing to the root of the server.
+  pub fn url(&self) -> String {
+    format!("https://127.0.0.1:{}/", self.port)
+  }
+
+  /// URL answered with the `status` redirect to the `target` URL.
+  pub fn redirect_url(&self, status: u16, target: &str) -> String {
+    let query: String = url::form_urlencoded::Serializer::new(String::new()).append_pair("to", target).finish();
+    format!("{}redirect/{}?{}", self.url(), status, query)
+  }
+
+  /// URL answered with the given response headers.
+  pub fn headers_url(&self, headers: &[(&str, &str)]) -> String {
+    let query: String = url::form_urlencoded::Serializer::new(String::new()).extend_pairs(headers).finish();
+    format!("{}headers?{}", self.url(), query)
+  }
+
+  /// Returns all the requests recorded so far.
+  pub fn requests(&self) -> Vec<RecordedRequest> {
+    self.requests.lock().unwrap().clone()
+  }
+}
+
+fn server_tls_config() -> rustls::ServerConfig {
+  let certified_key = rcgen::generate_simple_self_signed(vec!["localhost".to_string(), "127.0.0.1".to_string()]).unwrap();
+  let certificate = CertificateDer::from(certified_key.cert.der().to_vec());
+  let key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(certified_key.key_pair.serialize_der()));
+
+  let mut config = rustls::ServerConfig::builder_with_provider(Arc::new(rustls::crypto::aws_lc_rs::default_provider()))
+    .with_safe_default_protocol_versions()
+    .unwrap()
+    .with_no_client_auth()
+    .with_single_cert(vec![certificate], key)
+    .unwrap();
+
+  config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+  config
+}
+
+/// A TCP stream with the already consumed bytes (the ClientHello) put back in front of it.
+struct RewindStream {
+  prefix: Vec<u8>,
+  inner: TcpStream,
+}
+
+impl AsyncRead for RewindStream {
+  fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+    if !self.prefix.is_empty() {
+      let length = self.prefix.len().min(buf.remaining());
+      buf.put_slice(&self.prefix[..length]);
+      self.prefix.drain(..length);
+      return Poll::Ready(Ok(()));
+    }
+
+    Pin::new(&mut self.inner).poll_read(cx, buf)
+  }
+}
+
+impl AsyncWrite for RewindStream {
+  fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+    Pin::new(&mut self.inner).poll_write(cx, buf)
+  }
+
+  fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    Pin::new(&mut self.inner).poll_flush(cx)
+  }
+
+  fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    Pin::new(&mut self.inner).poll_shutdown(cx)
+  }
+}
+
+/// Reads from the stream until the whole ClientHello is available.
+async fn read_client_hello(stream: &mut TcpStream) -> io::Result<(Vec<u8>, ClientHello)> {
+  let mut buffer: Vec<u8> = vec![];
+
+  loop {
+    let mut chunk = [0u8; 4096];
+    let length = stream.read(&mut chunk).await?;
+
+    if length == 0 {
+      return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+
+    buffer.extend_from_slice(&chunk[..length]);
+
+    match ClientHello::parse(&buffer) {
+      Ok(client_hello) => return Ok((buffer, client_hello)),
+      Err(ClientHelloError::Truncated) => continue,
+      Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", e))),
+    }
+  }
+}
+
+async fn serve_connection(mut stream: TcpStream, acceptor: TlsAcceptor, requests: Arc<Mutex<Vec<RecordedRequest>>>) -> io::Result<()> {
+  let (prefix, client_hello) = read_client_hello(&mut stream).await?;
+  let stream = acceptor.accept(RewindStream { prefix, inner: stream }).await?;
+
+  let connection = RecordedConnection {
+    client_hello,
+    settings: vec![],
+    window_update_increment: None,
+    priority_frames: vec![],
+  };
+
+  if stream.get_ref().1.alpn_protocol() == Some(b"http/1.1") {
+    return serve_http1(stream, connection, requests).await;
+  }
+
+  serve_h2(stream, connection, requests).await
+}
+
+async fn serve_http1<S: AsyncRead + AsyncWrite + Unpin>(mut stream: S, connection: RecordedConnection, requests: Arc<Mutex<Vec<RecordedRequest>>>) -> io::Result<()> {
+  let mut buffer: Vec<u8> = vec![];
+
+  loop {
+    let head_end = loop {
+      if let Some(position) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+        break position;
+      }
+
+      let mut chunk = [0u8; 4096];
+      let length = stream.read(&mut chunk).await?;
+
+      if length == 0 {
+        return Ok(());
+      }
+
+      buffer.extend_from_slice(&chunk[..length]);
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..head_end]).to_string();
+    buffer.drain(..head_end + 4);
+
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split(' ');
+
+    let mut request = RecordedRequest {
+      pseudo_headers: vec![],
+      method: request_line.next().unwrap_or_default().to_string(),
+      path: request_line.next().unwrap_or_default().to_string(),
+      headers: vec![],
+      http1: true,
+      priority: None,
+      connection: connection.clone(),
+    };
+
+    for line in lines {
+      if let Some((name, value)) = line.split_once(':') {
+        request.headers.push((name.to_string(), value.trim().to_string()));
+      }
+    }
+
+    let content_length: usize = request.header("content-length")
+      .and_then(|length| length.parse().ok())
+      .unwrap_or(0);
+
+    while buffer.len() < content_length {
+      let mut chunk = [0u8; 4096];
+      let length = stream.read(&mut chunk).await?;
+
+      if length == 0 {
+        return Ok(());
+      }
+
+      buffer.extend_from_slice(&chunk[..length]);
+    }
+    buffer.drain(..content_length);
+
+    let (status, response_headers) = response_for(&request.path);
+    requests.lock().unwrap().push(request);
+
+    let response_headers: String = response_headers.iter().map(|(name, value)| format!("{}: {}\r\n", name, value)).collect();
+    stream.write_all(format!("HTTP/1.1 {} OK\r\n{}content-length: 0\r\n\r\n", status, response_headers).as_bytes()).await?;
+    stream.flush().await?;
+  }
+}
+
+/// Returns the response status and headers for the request path.
+fn response_for(path: &str) -> (u16, Vec<(String, String)>) {
+  let (path, query) = path.split_once('?').unwrap_or((path, ""));
+  let mut parameters = url::form_urlencoded::parse(query.as_bytes())
+    .map(|(name, value)| (name.to_string(), value.to_string()));
+
+  if let Some(status) = path.strip_prefix("/redirect/") {
+    let location = parameters.find(|(name, _)| name == "to").map(|(_, value)| value);
+    return (status.parse().unwrap_or(302), location.into_iter().map(|location| (String::from("location"), location)).collect());
+  }
+
+  if path == "/headers" {
+    return (200, parameters.collect());
+  }
+
+  (200, vec![])
+}
+
+async fn write_frame<S: AsyncWrite + Unpin>(stream: &mut S, kind: u8, flags: u8, stream_id: u32, payload: &[u8]) -> io::Result<()> {
+  let length = (payload.len() as u32).to_be_bytes();
+
+  let mut frame = vec![length[1], length[2], length[3], kind, flags];
+  frame.extend_from_slice(&stream_id.to_be_bytes());
+  frame.extend_from_slice(payload);
+
+  stream.write_all(&frame).await?;
+  stream.flush().await
+}
+
+/// Splits the `HEADERS` frame payload to the priority fields and the header block fragment.
+fn parse_headers_payload(flags: u8, payload: &[u8]) -> (Option<Priority>, Vec<u8>) {
+  let mut start = 0;
+  let mut end = payload.len();
+  let mut priority = None;
+
+  if flags & FLAG_PADDED != 0 {
+    start += 1;
+    end -= payload[0] as usize;
+  }
+
+  if flags & FLAG_PRIORITY != 0 {
+    priority = Some(Priority::parse(&payload[start..start + 5]));
+    start += 5;
+  }
+
+  (priority, payload[start..end].to_vec())
+}
+
+async fn serve_h2<S: AsyncRead + AsyncWrite + Unpin>(mut stream: S, mut connection: RecordedConnection, requests: Arc<Mutex<Vec<RecordedRequest>>>) -> io::Result<()> {
+  let mut preface = [0u8; PREFACE_LENGTH];
+  stream.read_exact(&mut preface).await?;
+
+  write_frame(&mut stream, FRAME_SETTINGS, 0, 0, &[]).await?;
+
+  let mut decoder = hpack::Decoder::new();
+  let mut encoder = hpack::Encoder::new();
+  let mut header_block: Vec<u8> = vec![];
+  let mut header_priority: Option<Priority> = None;
+  let mut settings_received = false;
+
+  loop {
+    let mut frame_header = [0u8; 9];
+    if stream.read_exact(&mut frame_header).await.is_err() {
+      return Ok(());
+    }
+
+    let length = u32::from_be_bytes([0, frame_header[0], frame_header[1], frame_header[2]]) as usize;
+    let kind = frame_header[3];
+    let flags = frame_header[4];
+    let stream_id = u32::from_be_bytes([frame_header[5], frame_header[6], frame_header[7], frame_header[8]]) & 0x7FFF_FFFF;
+
+    let mut payload = vec![0u8; length];
+    stream.read_exact(&mut payload).await?;
+
+    match kind {
+      FRAME_SETTINGS if flags & FLAG_ACK == 0 => {
+        if !settings_received {
+          connection.settings = payload.chunks_exact(6)
+            .map(|setting| (u16::from_be_bytes([setting[0], setting[1]]), u32::from_be_bytes([setting[2], setting[3], setting[4], setting[5]])))
+            .collect();
+          settings_received = true;
+        }
+
+        write_frame(&mut stream, FRAME_SETTINGS, FLAG_ACK, 0, &[]).await?;
+      },
+      FRAME_WINDOW_UPDATE if stream_id == 0 && connection.window_update_increment.is_none() => {
+        connection.window_update_increment = Some(u32::from_be_bytes([payload[0], payload[1], payload[2], payload[3]]) & 0x7FFF_FFFF);
+      },
+      FRAME_PRIORITY => {
+        connection.priority_frames.push((stream_id, Priority::parse(&payload)));
+      },
+      FRAME_PING if flags & FLAG_ACK == 0 => {
+        write_frame(&mut stream, FRAME_PING, FLAG_ACK, 0, &payload).await?;
+      },
+      FRAME_GOAWAY => return Ok(()),
+      FRAME_HEADERS | FRAME_CONTINUATION => {
+        if kind == FRAME_HEADERS {
+          (header_priority, header_block) = parse_headers_payload(flags, &payload);
+        } else {
+          header_block.extend_from_slice(&payload);
+        }
+
+        if flags & FLAG_END_HEADERS == 0 {
+          continue;
+        }
+
+        let decoded = decoder.decode(&header_block)
+          .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", e)))?;
+
+        let mut request = RecordedRequest {
+          pseudo_headers: vec![],
+          method: String::new(),
+          path: String::new(),
+          headers: vec![],
+          http1: false,
+          priority: header_priority,
+          connection: connection.clone(),
+        };
+
+        for (name, value) in decoded {
+          let name = String::from_utf8_lossy(&name).to_string();
+          let value = String::from_utf8_lossy(&value).to_string();
+
+          match name.as_str() {
+            ":method" => request.method = value.clone(),
+            ":path" => request.path = value.clone(),
+            _ => {},
+          }
+
+          if name.starts_with(':') {
+            request.pseudo_headers.push(name);
+          } else {
+            request.headers.push((name, value));
+          }
+        }
+        let (status, headers) = response_for(&request.path);
+        requests.lock().unwrap().push(request);
+
+        let mut response_headers = vec![(b":status".to_vec(), status.to_string().into_bytes())];
+        for (name, value) in headers {
+          response_headers.push((name.to_ascii_lowercase().into_bytes(), value.into_bytes()));
+        }
+        response_headers.push((b"content-length".to_vec(), b"0".to_vec()));
+
+        let response = encoder.encode(&response_headers);
+        write_frame(&mut stream, FRAME_HEADERS, FLAG_END_HEADERS | FLAG_END_STREAM, stream_id, &response).await?;
+      },
+      _ => {},
+    }
+  }
+}
@@ -0,0 +1,151 @@
+//! Reads the ClientHello of a QUIC handshake. The Initial packets are only protected with the keys derived
+//! from the client's destination connection ID (RFC 9001, section 5.2), so a test can decrypt them without a QUIC server.
+
+use std::collections::BTreeMap;
+
+use impit::emulation::ClientHello;
+use rustls::crypto::aws_lc_rs::cipher_suite::TLS13_AES_128_GCM_SHA256;
+use rustls::quic::{Keys, Version};
+use rustls::Side;
+use tokio::net::UdpSocket;
+
+const PACKET_TYPE_INITIAL: u8 = 0x0;
+
+const FRAME_PADDING: u64 = 0x0;
+const FRAME_PING: u64 = 0x1;
+const FRAME_CRYPTO: u64 = 0x6;
+
+/// Receives the client's datagrams until the whole ClientHello arrives, and parses it.
+pub async fn receive_client_hello(socket: &UdpSocket) -> ClientHello {
+  // The CRYPTO frame data by offset, the ClientHello can span several packets.
+  let mut fragments: BTreeMap<u64, Vec<u8>> = BTreeMap::new();
+  let mut buffer = [0u8; 65536];
+
+  loop {
+    let (length, _) = socket.recv_from(&mut buffer).await.unwrap();
+
+    // A datagram can carry several (coalesced) packets.
+    let mut offset = 0;
+    while offset < length {
+      let Some(packet_length) = read_initial_packet(&mut buffer[offset..length], &mut fragments) else {
+        break;
+      };
+
+      offset += packet_length;
+    }
+
+    // The handshake message starts with its type and 24-bit length.
+    let handshake = reassemble(&fragments);
+    if handshake.len() >= 4 {
+      let message_length = u32::from_be_bytes([0, handshake[1], handshake[2], handshake[3]]) as usize;
+
+      if handshake.len() >= 4 + message_length {
+        return ClientHello::parse(&handshake[..4 + message_length]).unwrap();
+      }
+    }
+  }
+}
+
+/// Decrypts the packet at the start of the datagram and collects its CRYPTO frames if it's an Initial packet.
+///
+/// Returns the length of the packet, or `None` for the short header packets, which take the rest of the datagram.
+fn read_initial_packet(packet: &mut [u8], fragments: &mut BTreeMap<u64, Vec<u8>>) -> Option<usize> {
+  let first = packet[0];
+  if first & 0x80 == 0 {
+    return None;
+  }
+
+  // The first byte and the version.
+  let mut offset = 5;
+
+  let dcid_length = packet[offset] as usize;
+  let dcid = packet[offset + 1..offset + 1 + dcid_length].to_vec();
+  offset += 1 + dcid_length;
+
+  let scid_length = packet[offset] as usize;
+  offset += 1 + scid_length;
+
+  let packet_type = (first >> 4) & 0x3;
+  if packet_type == PACKET_TYPE_INITIAL {
+    let token_length = varint(packet, &mut offset) as usize;
+    offset += token_length;
+  }
+
+  let length = varint(packet, &mut offset) as usize;
+  let packet_number_offset = offset;
+  let end = packet_number_offset + length;
+
+  if packet_type != PACKET_TYPE_INITIAL {
+    return Some(end);
+  }
+
+  let suite = TLS13_AES_128_GCM_SHA256.tls13().unwrap();
+  let keys = Keys::initial(Version::V1, suite, suite.quic, &dcid, Side::Server);
+
+  // The header protection sample starts 4 bytes after the start of the packet number.
+  let sample = packet[packet_number_offset + 4..packet_number_offset + 20].to_vec();
+  let (first_byte, rest) = packet.split_at_mut(1);
+  keys.remote.header
+    .decrypt_in_place(&sample, &mut first_byte[0], &mut rest[packet_number_offset - 1..packet_number_offset + 3])
+    .unwrap();
+
+  let packet_number_length = (packet[0] & 0x3) as usize + 1;
+  let packet_number = packet[packet_number_offset..packet_number_offset + packet_number_length].iter()
+    .fold(0u64, |number, byte| number << 8 | *byte as u64);
+
+  let (header, payload) = packet[..end].split_at_mut(packet_number_offset + packet_number_length);
+  let frames = keys.remote.packet.decrypt_in_place(packet_number, header, payload).unwrap();
+
+  let mut offset = 0;
+  while offset < frames.len() {
+    match varint(frames, &mut offset) {
+      FRAME_PADDING | FRAME_PING => {},
+      FRAME_CRYPTO => {
+        let data_offset = varint(frames, &mut offset);
+        let data_length = varint(frames, &mut offset) as usize;
+
+        fragments.insert(data_offset, frames[offset..offset + data_length].to_vec());
+        offset += data_length;
+      },
+      // The client doesn't send anything else before hearing from the server.
+      _ => break,
+    }
+  }
+
+  Some(end)
+}
+
+/// Joins the fragments received so far, up to the first gap.
+fn reassemble(fragments: &BTreeMap<u64, Vec<u8>>) -> Vec<u8> {
+  let mut data: Vec<u8> = vec![];
+
+  for (offset, fragment) in fragments {
+    let offset = *offset as usize;
+    if offset > data.len() {
+      break;
+    }
+
+    if offset + fragment.len() > data.len() {
+      data.extend_from_slice(&fragment[data.len() - offset..]);
+    }
+  }
+
+  data
+}
+
+/// Reads a QUIC variable-length integer (RFC 9000, section 16).
+fn varint(bytes: &[u8], offset: &mut usize) -> u64 {
+  let length = 1 << (bytes[*offset] >> 6);
+  let value = bytes[*offset..*offset + length].iter()
+    .enumerate()
+    .fold(0u64, |value, (index, byte)| {
+      let byte = match index {
+        0 => byte & 0x3f,
+        _ => *byte,
+      };
+      value << 8 | byte as u64
+    });
+
+  *offset += length;
+  value
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use common::TestServer;
+use impit::emulation::{Browser, ChromeVersion};
+use impit::impit::Impit;
+use impit::request::HeaderPosition;
+
+#[tokio::test]
+async fn custom_headers_keep_their_order_and_position() {
+  let server = TestServer::start().await;
+
+  let impit = Impit::builder()
+    .with_browser(Browser::Chrome(ChromeVersion::V131))
+    .with_ignore_tls_errors(true)
+    .build();
+
+  impit.request(impit::request::Method::GET, server.url())
+    .header("accept", "application/json")
+    .header("x-tag", "a")
+    .header("x-tag", "b")
+    .header_at("x-first", "1", HeaderPosition::Start)
+    .header_at("x-trace", "1", HeaderPosition::After("user-agent".to_string()))
+    .remove_header("priority")
+    .send()
+    .await
+    .unwrap();
+
+  let request = server.requests().pop().unwrap();
+  let names: Vec<&str> = request.headers.iter().map(|(name, _)| name.as_str()).collect();
+
+  assert_eq!(names.first(), Some(&"x-first"));
+  assert_eq!(request.header("accept"), Some("application/json"));
+  assert!(!names.contains(&"priority"));
+
+  let user_agent = names.iter().position(|name| *name == "user-agent").unwrap();
+  assert_eq!(names[user_agent + 1], "x-trace");
+
+  let tags: Vec<&str> = request.headers.iter()
+    .filter(|(name, _)| name == "x-tag")
+    .map(|(_, value)| value.as_str())
+    .collect();
+  assert_eq!(tags, ["a", "b"]);
+}
+
+#[tokio::test]
+async fn repeated_headers_are_sent_together() {
+  let server = TestServer::start().await;
+
+  let impit = Impit::builder()
+    .with_browser(Browser::Chrome(ChromeVersion::V131))
+    .with_ignore_tls_errors(true)
+    .build();
+
+  impit.request(impit::request::Method::GET, server.url())
+    .header_at("x-tag", "a", HeaderPosition::Start)
+    .header_at("x-tag", "b", HeaderPosition::End)
+    .send()
+    .await
+    .unwrap();
+
+  let request = server.requests().pop().unwrap();
+
+  // The second value is moved from the end to the first one.
+  assert_eq!(request.headers[0], (String::from("x-tag"), String::from("a")));
+  assert_eq!(request.headers[1], (String::from("x-tag"), String::from("b")));
+}
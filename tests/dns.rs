@@ -0,0 +1,146 @@
+mod common;
+
+use std::net::Ipv4Addr;
+use std::time::Duration;
+
+use common::dns::TestDnsServer;
+use common::quic;
+use hickory_proto::rr::rdata::svcb::{Alpn, EchConfig, IpHint, SvcParamKey, SvcParamValue, SVCB};
+use hickory_proto::rr::rdata::A;
+use hickory_proto::rr::{Name, RecordType};
+use impit::dns::{DnsConfig, NameServer};
+use impit::impit::{ErrorType, Impit};
+use tokio::net::UdpSocket;
+
+#[tokio::test]
+async fn http3_discovery_uses_the_configured_nameservers() {
+  let dns = TestDnsServer::start(&["h3.impit.test"]).await;
+
+  let impit = Impit::builder()
+    .with_http3()
+    .with_dns(DnsConfig::NameServers(vec![NameServer::udp(dns.address())]))
+    .with_fallback_to_vanilla(false)
+    .with_default_timeout(Duration::from_secs(5))
+    .build();
+
+  // The `.test` hosts have no addresses, but the HTTPS record lookup decides which protocol is tried.
+  let h3 = impit.get("https://h3.impit.test/", None).await;
+  let tcp = impit.get("https://tcp.impit.test/", None).await;
+
+  assert!(matches!(h3, Err(ErrorType::Http3Failure(_))), "{:?}", h3.map(|response| response.status()));
+  assert!(matches!(tcp, Err(ErrorType::ConnectError(_))), "{:?}", tcp.map(|response| response.status()));
+
+  let queries = dns.queries();
+  assert!(queries.contains(&(String::from("h3.impit.test"), RecordType::HTTPS)));
+  assert!(queries.contains(&(String::from("tcp.impit.test"), RecordType::HTTPS)));
+  // The addresses to connect to are looked up with the same nameservers.
+  assert!(queries.contains(&(String::from("tcp.impit.test"), RecordType::A)));
+}
+
+#[tokio::test]
+async fn ip_addresses_are_not_looked_up() {
+  let dns = TestDnsServer::start(&[]).await;
+
+  let impit = Impit::builder()
+    .with_http3()
+    .with_dns(DnsConfig::NameServers(vec![NameServer::udp(dns.address())]))
+    .with_default_timeout(Duration::from_secs(5))
+    .build();
+
+  let _ = impit.get("https://127.0.0.1:9/", None).await;
+
+  assert!(dns.queries().is_empty());
+}
+
+#[tokio::test]
+async fn https_records_point_http3_to_the_service_endpoint() {
+  // Stands in for the HTTP/3 server, only receiving the QUIC handshake.
+  let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+  let port = socket.local_addr().unwrap().port();
+
+  let dns = TestDnsServer::start_with_records(vec![
+    (String::from("alias.impit.test"), SVCB::new(0, Name::from_ascii("svc.impit.test.").unwrap(), vec![])),
+    (String::from("svc.impit.test"), SVCB::new(1, Name::root(), vec![
+      (SvcParamKey::Alpn, SvcParamValue::Alpn(Alpn(vec![String::from("h3")]))),
+      (SvcParamKey::Port, SvcParamValue::Port(port)),
+      (SvcParamKey::Ipv4Hint, SvcParamValue::Ipv4Hint(IpHint(vec![A(Ipv4Addr::LOCALHOST)]))),
+    ])),
+  ]).await;
+
+  let impit = Impit::builder()
+    .with_http3()
+    .with_dns(DnsConfig::NameServers(vec![NameServer::udp(dns.address())]))
+    .with_default_timeout(Duration::from_secs(1))
+    .build();
+
+  // There is no HTTP/3 server, the request times out.
+  assert!(impit.get(format!("https://alias.impit.test:{}/", port), None).await.is_err());
+
+  // The alias is followed, `svc.impit.test` has no addresses, so the hint from the record is used.
+  let mut buffer = [0u8; 2048];
+  let received = tokio::time::timeout(Duration::from_secs(1), socket.recv_from(&mut buffer)).await;
+  assert!(received.is_ok_and(|received| received.is_ok()));
+
+  let queries = dns.queries();
+  assert!(queries.contains(&(String::from("svc.impit.test"), RecordType::HTTPS)), "{:?}", queries);
+  assert!(queries.contains(&(String::from("svc.impit.test"), RecordType::A)), "{:?}", queries);
+}
+
+/// An `ECHConfigList` with a single configuration for the public name (DHKEM(X25519), HKDF-SHA256 and AES-128-GCM).
+fn ech_config_list(public_name: &str) -> Vec<u8> {
+  // The X25519 base point. Nothing decrypts the inner ClientHello, any valid public key works.
+  let mut public_key = [0u8; 32];
+  public_key[0] = 9;
+
+  let mut contents = vec![1];
+  contents.extend_from_slice(&0x0020u16.to_be_bytes());
+  contents.extend_from_slice(&(public_key.len() as u16).to_be_bytes());
+  contents.extend_from_slice(&public_key);
+  contents.extend_from_slice(&[0, 4, 0, 1, 0, 1]);
+  // maximum_name_length
+  contents.push(0);
+  contents.push(public_name.len() as u8);
+  contents.extend_from_slice(public_name.as_bytes());
+  // No extensions.
+  contents.extend_from_slice(&[0, 0]);
+
+  let mut config = 0xfe0du16.to_be_bytes().to_vec();
+  config.extend_from_slice(&(contents.len() as u16).to_be_bytes());
+  config.extend(contents);
+
+  let mut list = (config.len() as u16).to_be_bytes().to_vec();
+  list.extend(config);
+  list
+}
+
+#[tokio::test]
+async fn https_record_ech_config_encrypts_the_http3_client_hello() {
+  let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+  let port = socket.local_addr().unwrap().port();
+
+  let dns = TestDnsServer::start_with_records(vec![
+    (String::from("ech.impit.test"), SVCB::new(1, Name::root(), vec![
+      (SvcParamKey::Alpn, SvcParamValue::Alpn(Alpn(vec![String::from("h3")]))),
+      (SvcParamKey::Ipv4Hint, SvcParamValue::Ipv4Hint(IpHint(vec![A(Ipv4Addr::LOCALHOST)]))),
+      (SvcParamKey::EchConfig, SvcParamValue::EchConfig(EchConfig(ech_config_list("public.impit.test")))),
+    ])),
+  ]).await;
+
+  let impit = Impit::builder()
+    .with_http3()
+    .with_dns(DnsConfig::NameServers(vec![NameServer::udp(dns.address())]))
+    .with_default_timeout(Duration::from_secs(1))
+    .build();
+
+  let (response, client_hello) = tokio::join!(
+    impit.get(format!("https://ech.impit.test:{}/", port), None),
+    tokio::time::timeout(Duration::from_secs(1), quic::receive_client_hello(&socket)),
+  );
+
+  assert!(response.is_err());
+  let client_hello = client_hello.unwrap();
+
+  // The outer ClientHello is sent to the public name of the ECH configuration, the origin's name is encrypted.
+  assert_eq!(client_hello.server_name.as_deref(), Some("public.impit.test"));
+  assert!(client_hello.extensions.contains(&0xfe0d));
+}
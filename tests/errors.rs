@@ -0,0 +1,40 @@
+use impit::impit::{ErrorType, Impit};
+
+#[tokio::test]
+async fn invalid_urls_return_errors() {
+  let impit = Impit::builder().build();
+
+  let error = impit.get("not a url".to_string(), None).await.unwrap_err();
+  assert!(matches!(error, ErrorType::UrlParsingError), "unexpected error: {:?}", error);
+
+  let error = impit.get("data:text/plain,hello".to_string(), None).await.unwrap_err();
+  assert!(matches!(error, ErrorType::UrlMissingHostnameError), "unexpected error: {:?}", error);
+
+  let error = impit.get("ftp://example.com/file".to_string(), None).await.unwrap_err();
+  assert!(matches!(error, ErrorType::UrlProtocolError), "unexpected error: {:?}", error);
+}
+
+#[test]
+fn invalid_proxy_url_fails_the_build() {
+  let impit = Impit::builder()
+    .with_proxy("http://[invalid".to_string())
+    .try_build();
+
+  assert!(matches!(impit, Err(ErrorType::ProxyUrlError(_))));
+}
+
+#[tokio::test]
+async fn refused_connection_is_a_connect_error() {
+  let impit = Impit::builder().build();
+
+  // Nothing listens on port 1, the connection is refused right away.
+  let error = impit.get("https://127.0.0.1:1/".to_string(), None).await.unwrap_err();
+
+  assert!(matches!(error, ErrorType::ConnectError(_)), "unexpected error: {:?}", error);
+  assert!(std::error::Error::source(&error).is_some());
+}
+
+#[test]
+fn errors_are_displayable() {
+  assert_eq!(ErrorType::UrlParsingError.to_string(), "the URL couldn't be parsed");
+}
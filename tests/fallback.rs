@@ -0,0 +1,68 @@
+mod common;
+
+use common::TestServer;
+use impit::emulation::{Browser, BrowserProfile, ChromeVersion};
+use impit::impit::{ErrorType, Impit, ServedBy};
+
+/// A Chrome profile the test server refuses - it offers no ALPN protocol the server speaks,
+/// so the server aborts the handshake with the `no_application_protocol` alert.
+fn refused_profile() -> BrowserProfile {
+  let mut profile = Browser::Chrome(ChromeVersion::V131).profile();
+  profile.tls.alpn = vec![String::from("spdy/3.1")];
+  profile
+}
+
+#[tokio::test]
+async fn failed_handshake_falls_back_to_vanilla_client() {
+  let server = TestServer::start().await;
+
+  let impit = Impit::builder()
+    .with_browser_profile(refused_profile())
+    .with_ignore_tls_errors(true)
+    .with_fallback_to_vanilla(true)
+    .build();
+
+  let response = impit.get(server.url(), None).await.unwrap();
+
+  assert_eq!(response.status(), 200);
+  assert_eq!(response.extensions().get::<ServedBy>(), Some(&ServedBy::VanillaFallback));
+
+  // Only the fallback request got through, without the browser headers.
+  let requests = server.requests();
+  assert_eq!(requests.len(), 1);
+  assert_eq!(requests[0].header("sec-ch-ua"), None);
+}
+
+#[tokio::test]
+async fn failed_handshake_is_returned_without_fallback() {
+  let server = TestServer::start().await;
+
+  let impit = Impit::builder()
+    .with_browser_profile(refused_profile())
+    .with_ignore_tls_errors(true)
+    .with_fallback_to_vanilla(false)
+    .build();
+
+  let error = impit.get(server.url(), None).await.unwrap_err();
+
+  assert!(
+    matches!(error, ErrorType::TlsHandshakeError { alert: Some(rustls::AlertDescription::NoApplicationProtocol), .. }),
+    "unexpected error: {:?}",
+    error,
+  );
+  assert!(server.requests().is_empty());
+}
+
+#[tokio::test]
+async fn impersonated_responses_are_marked() {
+  let server = TestServer::start().await;
+
+  let impit = Impit::builder()
+    .with_browser(Browser::Chrome(ChromeVersion::V131))
+    .with_ignore_tls_errors(true)
+    .build();
+
+  let response = impit.get(server.url(), None).await.unwrap();
+
+  assert_eq!(response.extensions().get::<ServedBy>(), Some(&ServedBy::Impersonated));
+}
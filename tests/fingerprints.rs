@@ -0,0 +1,114 @@
+//! Compares the fingerprints of the built-in browsers with the golden files in `tests/golden`.
+//!
+//! Each golden file describes what the local test server sees from one browser preset - the TLS fingerprints,
+//! the HTTP/2 fingerprint (Akamai format) and the headers in the order they were sent.
+//!
+//! A missing golden file fails the test. After an intentional fingerprint change (or for a new browser preset),
+//! regenerate them with `IMPIT_UPDATE_GOLDEN=1 cargo test --test fingerprints` and review the diff.
+mod common;
+
+use std::path::PathBuf;
+
+use common::{RecordedRequest, TestServer};
+use impit::emulation::{Browser, ChromeVersion, FirefoxVersion, SafariVersion, TlsFingerprint};
+use impit::impit::Impit;
+
+const UPDATE_GOLDEN_ENV: &str = "IMPIT_UPDATE_GOLDEN";
+
+fn browsers() -> Vec<Browser> {
+  let mut browsers = vec![];
+
+  for version in [ChromeVersion::V125, ChromeVersion::V131] {
+    browsers.push(Browser::Chrome(version));
+    browsers.push(Browser::Edge(version));
+    browsers.push(Browser::ChromeAndroid(version));
+  }
+
+  for version in [FirefoxVersion::V128, FirefoxVersion::V133] {
+    browsers.push(Browser::Firefox(version));
+  }
+
+  for version in [SafariVersion::V17, SafariVersion::V18] {
+    browsers.push(Browser::Safari(version));
+    browsers.push(Browser::SafariIos(version));
+  }
+
+  browsers
+}
+
+fn golden_path(browser: &Browser) -> PathBuf {
+  let name = browser.profile().name.to_lowercase().replace(' ', "-");
+
+  PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+    .join("tests")
+    .join("golden")
+    .join(format!("{}.txt", name))
+}
+
+/// Renders the parts of the request that make up the browser fingerprint.
+///
+/// JA3 is left out, as Chrome shuffles the TLS extensions for every connection.
+fn render(request: &RecordedRequest) -> String {
+  let client_hello = &request.connection.client_hello;
+  let tls = TlsFingerprint::from_client_hello(client_hello);
+
+  let headers_priority = request.priority
+    .map(|priority| format!("{}:{}:{}", priority.stream_dependency, priority.exclusive as u8, priority.weight))
+    .unwrap_or(String::from("none"));
+
+  let mut lines = vec![
+    format!("ja3n: {}", tls.ja3n),
+    format!("ja4: {}", tls.ja4),
+    format!("alpn: {}", client_hello.alpn.join(",")),
+    format!("http2: {}", request.akamai_fingerprint()),
+    format!("headers_priority: {}", headers_priority),
+  ];
+
+  for (name, value) in &request.headers {
+    lines.push(format!("header: {}: {}", name, value));
+  }
+
+  lines.join("\n") + "\n"
+}
+
+#[tokio::test]
+async fn browser_fingerprints_match_golden_files() {
+  let server = TestServer::start().await;
+  let update = std::env::var(UPDATE_GOLDEN_ENV).is_ok();
+  let mut mismatches = vec![];
+
+  for browser in browsers() {
+    let impit = Impit::builder()
+      .with_browser(browser)
+      .with_ignore_tls_errors(true)
+      .build();
+
+    impit.get(server.url(), None).await.unwrap();
+
+    let request = server.requests().pop().unwrap();
+    let actual = render(&request);
+    let path = golden_path(&browser);
+
+    if update {
+      std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+      std::fs::write(&path, &actual).unwrap();
+      continue;
+    }
+
+    let Ok(expected) = std::fs::read_to_string(&path) else {
+      mismatches.push(format!("{}: missing golden file\n+++ actual\n{}", path.display(), actual));
+      continue;
+    };
+
+    if expected != actual {
+      mismatches.push(format!("{}:\n--- expected\n{}+++ actual\n{}", path.display(), expected, actual));
+    }
+  }
+
+  assert!(
+    mismatches.is_empty(),
+    "Fingerprints differ from the golden files (run with {}=1 to update them):\n\n{}",
+    UPDATE_GOLDEN_ENV,
+    mismatches.join("\n"),
+  );
+}
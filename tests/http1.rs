@@ -0,0 +1,77 @@
+mod common;
+
+use common::TestServer;
+use impit::emulation::{Browser, ChromeVersion, FirefoxVersion};
+use impit::impit::Impit;
+
+#[tokio::test]
+async fn http1_requests_use_the_browser_header_casing_and_order() {
+  let server = TestServer::start().await;
+
+  let chrome = Impit::builder()
+    .with_browser(Browser::Chrome(ChromeVersion::V131))
+    .with_ignore_tls_errors(true)
+    .with_http1_only()
+    .build();
+
+  chrome.get(server.url(), None).await.unwrap();
+
+  let request = server.requests().pop().unwrap();
+  assert!(request.http1);
+
+  let names: Vec<&str> = request.headers.iter().map(|(name, _)| name.as_str()).collect();
+  // Chrome title-cases its HTTP/1.1 headers, except for the client hints.
+  assert_eq!(&names[..5], ["Host", "Connection", "sec-ch-ua", "sec-ch-ua-mobile", "sec-ch-ua-platform"]);
+  assert!(names.contains(&"User-Agent"));
+  assert!(names.contains(&"Sec-Fetch-Dest"));
+  assert!(!names.iter().any(|name| name.eq_ignore_ascii_case("priority")));
+
+  let authority = server.url().trim_start_matches("https://").trim_end_matches('/').to_string();
+  assert_eq!(request.header("host"), Some(authority.as_str()));
+}
+
+#[tokio::test]
+async fn http1_requests_keep_the_firefox_header_order() {
+  let server = TestServer::start().await;
+
+  let firefox = Impit::builder()
+    .with_browser(Browser::Firefox(FirefoxVersion::V133))
+    .with_ignore_tls_errors(true)
+    .with_http1_only()
+    .build();
+
+  firefox.get(server.url(), None).await.unwrap();
+
+  let request = server.requests().pop().unwrap();
+  let names: Vec<&str> = request.headers.iter().map(|(name, _)| name.as_str()).collect();
+
+  assert_eq!(&names[..6], ["Host", "User-Agent", "Accept", "Accept-Language", "Accept-Encoding", "Connection"]);
+}
+
+#[tokio::test]
+async fn firefox_sends_the_connection_header_only_over_http1() {
+  let server = TestServer::start().await;
+
+  for version in [FirefoxVersion::V128, FirefoxVersion::V133] {
+    let http2 = Impit::builder()
+      .with_browser(Browser::Firefox(version))
+      .with_ignore_tls_errors(true)
+      .build();
+
+    let http1 = Impit::builder()
+      .with_browser(Browser::Firefox(version))
+      .with_ignore_tls_errors(true)
+      .with_http1_only()
+      .build();
+
+    http2.get(server.url(), None).await.unwrap();
+    let request = server.requests().pop().unwrap();
+    assert!(!request.http1);
+    assert_eq!(request.header("connection"), None, "{:?}", version);
+
+    http1.get(server.url(), None).await.unwrap();
+    let request = server.requests().pop().unwrap();
+    assert!(request.http1);
+    assert_eq!(request.header("connection"), Some("keep-alive"), "{:?}", version);
+  }
+}
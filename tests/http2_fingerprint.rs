@@ -0,0 +1,61 @@
+mod common;
+
+use common::{Priority, TestServer};
+use impit::emulation::{Browser, ChromeVersion, FirefoxVersion, SafariVersion};
+use impit::impit::Impit;
+
+const CHROME: &str = "1:65536;2:0;4:6291456;6:262144|15663105|0|m,a,s,p";
+const FIREFOX: &str = "1:65536;4:131072;5:16384|12517377|0|m,p,a,s";
+const SAFARI_17: &str = "2:0;4:4194304;3:100|10485760|0|m,s,p,a";
+const SAFARI_IOS_17: &str = "2:0;4:2097152;3:100|10485760|0|m,s,p,a";
+const SAFARI_18: &str = "2:0;3:100;4:2097152;9:1|10420225|0|m,s,a,p";
+
+fn presets() -> [(Browser, &'static str); 12] {
+  [
+    (Browser::Chrome(ChromeVersion::V125), CHROME),
+    (Browser::Chrome(ChromeVersion::V131), CHROME),
+    (Browser::Edge(ChromeVersion::V125), CHROME),
+    (Browser::Edge(ChromeVersion::V131), CHROME),
+    (Browser::ChromeAndroid(ChromeVersion::V125), CHROME),
+    (Browser::ChromeAndroid(ChromeVersion::V131), CHROME),
+    (Browser::Firefox(FirefoxVersion::V128), FIREFOX),
+    (Browser::Firefox(FirefoxVersion::V133), FIREFOX),
+    (Browser::Safari(SafariVersion::V17), SAFARI_17),
+    (Browser::Safari(SafariVersion::V18), SAFARI_18),
+    (Browser::SafariIos(SafariVersion::V17), SAFARI_IOS_17),
+    (Browser::SafariIos(SafariVersion::V18), SAFARI_18),
+  ]
+}
+
+#[test]
+fn built_in_profiles_have_the_browser_akamai_fingerprints() {
+  for (browser, akamai) in presets() {
+    assert_eq!(browser.http2_fingerprint().akamai_string(), akamai, "{:?}", browser);
+  }
+}
+
+#[tokio::test]
+async fn presets_send_their_fingerprint_on_the_wire() {
+  let server = TestServer::start().await;
+
+  for (browser, akamai) in presets() {
+    let fingerprint = browser.http2_fingerprint();
+
+    let impit = Impit::builder()
+      .with_browser(browser)
+      .with_ignore_tls_errors(true)
+      .build();
+
+    impit.get(server.url(), None).await.unwrap();
+
+    let request = server.requests().pop().unwrap();
+    assert_eq!(request.akamai_fingerprint(), akamai, "{:?}", browser);
+
+    let headers_priority = fingerprint.headers_priority.map(|priority| Priority {
+      stream_dependency: priority.stream_dependency,
+      exclusive: priority.exclusive,
+      weight: priority.weight,
+    });
+    assert_eq!(request.priority, headers_priority, "{:?}", browser);
+  }
+}
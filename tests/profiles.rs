@@ -0,0 +1,146 @@
+use impit::emulation::{Browser, BrowserProfile, ChromeVersion, FirefoxVersion, ProfileError};
+use impit::impit::{ErrorType, Impit};
+use impit::request::RequestContext;
+
+const TOML_PROFILE: &str = r#"
+name = "Chrome 131 (custom)"
+headers = [
+  ["user-agent", "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36"],
+  ["accept", "*/*"],
+]
+http1_headers = [
+  ["Host", ""],
+  ["Connection", "keep-alive"],
+  ["User-Agent", "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36"],
+  ["Accept", "*/*"],
+]
+
+[contexts.fetch]
+headers = [["accept", "*/*"], ["sec-fetch-mode", "cors"], ["sec-fetch-dest", "empty"]]
+removed_headers = ["upgrade-insecure-requests", "sec-fetch-user"]
+
+[tls]
+family = "chrome"
+version = 131
+cipher_suites = [4865, 4866, 4867]
+supported_groups = [29, 23]
+alpn = ["h2", "http/1.1"]
+
+[http2]
+settings = [["HEADER_TABLE_SIZE", 65536], ["ENABLE_PUSH", 0], ["INITIAL_WINDOW_SIZE", 6291456], ["MAX_HEADER_LIST_SIZE", 262144]]
+window_update_increment = 15663105
+priority_frames = []
+headers_priority = { stream_dependency = 0, exclusive = true, weight = 256 }
+pseudo_headers_order = [":method", ":authority", ":scheme", ":path"]
+"#;
+
+#[test]
+fn built_in_profiles_round_trip_through_json() {
+  for browser in [Browser::Chrome(ChromeVersion::V131), Browser::Firefox(FirefoxVersion::V133)] {
+    let profile = browser.profile();
+    let loaded = BrowserProfile::from_json(&profile.to_json()).unwrap();
+
+    assert_eq!(loaded, profile);
+  }
+}
+
+#[test]
+fn toml_profiles_are_loaded() {
+  let profile = BrowserProfile::from_toml(TOML_PROFILE).unwrap();
+
+  assert_eq!(profile.name, "Chrome 131 (custom)");
+  assert_eq!(profile.headers[1], (String::from("accept"), String::from("*/*")));
+  assert_eq!(profile.tls.cipher_suites, [0x1301, 0x1302, 0x1303]);
+  assert_eq!(profile.http2.akamai_string(), "1:65536;2:0;4:6291456;6:262144|15663105|0|m,a,s,p");
+  assert!(profile.contexts.contains_key(&RequestContext::Fetch));
+}
+
+#[test]
+fn profiles_are_loaded_from_files() {
+  let directory = std::env::temp_dir();
+  let toml_path = directory.join(format!("impit-profile-{}.toml", std::process::id()));
+  let json_path = directory.join(format!("impit-profile-{}.json", std::process::id()));
+
+  let profile = BrowserProfile::from_toml(TOML_PROFILE).unwrap();
+  std::fs::write(&toml_path, TOML_PROFILE).unwrap();
+  std::fs::write(&json_path, profile.to_json()).unwrap();
+
+  assert_eq!(BrowserProfile::from_file(&toml_path).unwrap(), profile);
+  assert_eq!(BrowserProfile::from_file(&json_path).unwrap(), profile);
+
+  std::fs::remove_file(toml_path).unwrap();
+  std::fs::remove_file(json_path).unwrap();
+
+  let missing = BrowserProfile::from_file(directory.join("impit-missing-profile.json"));
+  assert!(matches!(missing, Err(ProfileError::IoError(_))));
+}
+
+#[test]
+fn malformed_profiles_are_rejected() {
+  let json = BrowserProfile::from_json("{\"name\": \"Broken\"");
+  assert!(matches!(json, Err(ProfileError::JsonError(_))), "{:?}", json);
+
+  let toml = BrowserProfile::from_toml(&TOML_PROFILE.replace("family = \"chrome\"", "family = \"netscape\""));
+  assert!(matches!(toml, Err(ProfileError::TomlError(_))), "{:?}", toml);
+}
+
+#[test]
+fn invalid_headers_are_rejected() {
+  let invalid = [
+    // A space in the header name.
+    TOML_PROFILE.replace("[\"accept\", \"*/*\"],\n]", "[\"x custom\", \"*/*\"],\n]"),
+    // A newline in the header value.
+    TOML_PROFILE.replace("[\"Accept\", \"*/*\"]", "[\"Accept\", \"*/*\\nX-Injected: 1\"]"),
+    // The context headers are checked too.
+    TOML_PROFILE.replace("[\"sec-fetch-mode\", \"cors\"]", "[\"sec-fetch-mode:\", \"cors\"]"),
+  ];
+
+  for toml in invalid {
+    let error = BrowserProfile::from_toml(&toml).unwrap_err();
+    assert!(matches!(error, ProfileError::InvalidHeader { .. }), "{:?}", error);
+  }
+
+  let mut profile = Browser::Chrome(ChromeVersion::V131).profile();
+  profile.headers.push((String::from("x-custom"), String::from("line\r\nbreak")));
+
+  let error = BrowserProfile::from_json(&profile.to_json()).unwrap_err();
+  assert_eq!(error.to_string(), "invalid header \"x-custom\": \"line\\r\\nbreak\"");
+}
+
+#[test]
+fn unknown_cipher_suites_and_groups_are_rejected() {
+  let error = BrowserProfile::from_toml(&TOML_PROFILE.replace("4867]", "4867, 65535]")).unwrap_err();
+  assert!(matches!(error, ProfileError::UnsupportedCipherSuite(0xffff)), "{:?}", error);
+  assert_eq!(error.to_string(), "unsupported cipher suite 0xffff");
+
+  let error = BrowserProfile::from_toml(&TOML_PROFILE.replace("[29, 23]", "[29, 23, 12345]")).unwrap_err();
+  assert!(matches!(error, ProfileError::UnsupportedGroup(12345)), "{:?}", error);
+}
+
+#[test]
+fn invalid_profiles_built_in_code_fail_the_build() {
+  let mut profile = Browser::Firefox(FirefoxVersion::V133).profile();
+  profile.tls.supported_groups.push(0x9999);
+
+  let impit = Impit::builder()
+    .with_browser_profile(profile)
+    .try_build();
+
+  assert!(matches!(impit, Err(ErrorType::InvalidProfile(ProfileError::UnsupportedGroup(0x9999)))));
+}
+
+#[test]
+fn tls_options_the_emulator_ignores_are_rejected() {
+  let options = [
+    ("key_shares", "key_shares = [29]"),
+    ("signature_algorithms", "signature_algorithms = [1027, 2052]"),
+    ("extensions_order", "extensions_order = [0, 23, 65281]"),
+  ];
+
+  for (option, line) in options {
+    let toml = TOML_PROFILE.replace("alpn = [", &format!("{}\nalpn = [", line));
+    let error = BrowserProfile::from_toml(&toml).unwrap_err();
+
+    assert!(matches!(error, ProfileError::UnsupportedTlsOption(name) if name == option), "{:?}", error);
+  }
+}
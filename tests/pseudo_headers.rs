@@ -0,0 +1,50 @@
+mod common;
+
+use common::TestServer;
+use impit::emulation::{Browser, ChromeVersion, FirefoxVersion};
+use impit::impit::Impit;
+
+const REQUESTS_PER_CLIENT: usize = 10;
+
+#[tokio::test]
+async fn concurrent_clients_keep_their_pseudo_headers_order() {
+  let server = TestServer::start().await;
+
+  let chrome = Impit::builder()
+    .with_browser(Browser::Chrome(ChromeVersion::V131))
+    .with_ignore_tls_errors(true)
+    .build();
+
+  let firefox = Impit::builder()
+    .with_browser(Browser::Firefox(FirefoxVersion::V133))
+    .with_ignore_tls_errors(true)
+    .build();
+
+  tokio::join!(
+    async {
+      for _ in 0..REQUESTS_PER_CLIENT {
+        chrome.get(server.url(), None).await.unwrap();
+      }
+    },
+    async {
+      for _ in 0..REQUESTS_PER_CLIENT {
+        firefox.get(server.url(), None).await.unwrap();
+      }
+    },
+  );
+
+  let requests = server.requests();
+  assert_eq!(requests.len(), 2 * REQUESTS_PER_CLIENT);
+
+  for request in requests {
+    let user_agent = request.header("user-agent").unwrap();
+
+    let expected_order = if user_agent.contains("Firefox") {
+      [":method", ":path", ":authority", ":scheme"]
+    } else {
+      [":method", ":authority", ":scheme", ":path"]
+    };
+
+    assert_eq!(request.pseudo_headers, expected_order, "unexpected pseudo-header order for {}", user_agent);
+  }
+}
@@ -0,0 +1,168 @@
+mod common;
+
+use common::TestServer;
+use impit::emulation::{Browser, ChromeVersion};
+use std::sync::Arc;
+
+use impit::impit::{ErrorType, Impit, RedirectBehavior};
+use impit::redirect::{RedirectAction, RedirectAttempt, RedirectHistory};
+use impit::request::Method;
+
+fn chrome() -> Impit {
+  Impit::builder()
+    .with_browser(Browser::Chrome(ChromeVersion::V131))
+    .with_ignore_tls_errors(true)
+    .build()
+}
+
+#[tokio::test]
+async fn see_other_changes_the_method_to_get() {
+  let server = TestServer::start().await;
+
+  let response = chrome().request(Method::POST, server.redirect_url(303, "/done"))
+    .json(&serde_json::json!({ "form": true }))
+    .send()
+    .await
+    .unwrap();
+
+  assert_eq!(response.status(), 200);
+  assert_eq!(response.url().path(), "/done");
+
+  let requests = server.requests();
+  assert_eq!(requests.len(), 2);
+
+  assert_eq!(requests[1].method, "GET");
+  assert_eq!(requests[1].path, "/done");
+  assert_eq!(requests[1].header("content-type"), None);
+  assert_eq!(requests[1].header("content-length"), None);
+}
+
+#[tokio::test]
+async fn temporary_redirect_replays_the_request() {
+  let server = TestServer::start().await;
+
+  chrome().request(Method::POST, server.redirect_url(307, "/done"))
+    .body("a=1")
+    .send()
+    .await
+    .unwrap();
+
+  let request = server.requests().pop().unwrap();
+
+  assert_eq!(request.method, "POST");
+  assert_eq!(request.path, "/done");
+  assert_eq!(request.header("content-length"), Some("3"));
+}
+
+#[tokio::test]
+async fn cross_origin_redirects_update_the_headers() {
+  let first = TestServer::start().await;
+  let second = TestServer::start().await;
+
+  chrome().request(Method::GET, first.redirect_url(302, &second.url()))
+    .referrer(format!("{}index.html", first.url()))
+    .header("authorization", "Bearer secret")
+    .send()
+    .await
+    .unwrap();
+
+  let before = first.requests().pop().unwrap();
+  let after = second.requests().pop().unwrap();
+
+  assert_eq!(before.header("authorization"), Some("Bearer secret"));
+  assert_eq!(before.header("sec-fetch-site"), Some("same-origin"));
+  assert_eq!(before.header("referer"), Some(format!("{}index.html", first.url()).as_str()));
+
+  // Both servers run on 127.0.0.1, only the ports differ.
+  assert_eq!(after.header("authorization"), None);
+  assert_eq!(after.header("sec-fetch-site"), Some("same-site"));
+  assert_eq!(after.header("referer"), Some(first.url().as_str()));
+}
+
+#[tokio::test]
+async fn redirect_limit_is_enforced() {
+  let server = TestServer::start().await;
+
+  let impit = Impit::builder()
+    .with_browser(Browser::Chrome(ChromeVersion::V131))
+    .with_ignore_tls_errors(true)
+    .with_redirect(RedirectBehavior::FollowRedirect(1))
+    .build();
+
+  let url = server.redirect_url(302, &server.redirect_url(302, "/done"));
+
+  match impit.get(url, None).await {
+    Err(ErrorType::TooManyRedirects { limit, url }) => {
+      assert_eq!(limit, 1);
+      assert_eq!(url.path(), "/done");
+    },
+    other => panic!("expected TooManyRedirects, got {:?}", other.map(|response| response.status())),
+  }
+
+  assert_eq!(server.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn manual_redirect_returns_the_redirect_response() {
+  let server = TestServer::start().await;
+
+  let impit = Impit::builder()
+    .with_ignore_tls_errors(true)
+    .with_redirect(RedirectBehavior::ManualRedirect)
+    .build();
+
+  let response = impit.get(server.redirect_url(301, "/done"), None).await.unwrap();
+
+  assert_eq!(response.status(), 301);
+  assert_eq!(server.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn responses_carry_the_redirect_history() {
+  let server = TestServer::start().await;
+
+  let second = server.redirect_url(301, "/done");
+  let first = server.redirect_url(302, &second);
+
+  let response = chrome().get(&first, None).await.unwrap();
+
+  let statuses: Vec<u16> = response.redirects().iter().map(|hop| hop.status.as_u16()).collect();
+  assert_eq!(statuses, [302, 301]);
+  assert_eq!(response.redirects()[0].headers.get("location").unwrap(), second.as_str());
+
+  let chain: Vec<&str> = response.url_chain().iter().map(|url| url.as_str()).collect();
+  assert_eq!(chain, [first.as_str(), second.as_str(), format!("{}done", server.url()).as_str()]);
+}
+
+#[tokio::test]
+async fn custom_redirect_behavior_decides_every_hop() {
+  let server = TestServer::start().await;
+
+  let stop_at_done = Impit::builder()
+    .with_ignore_tls_errors(true)
+    .with_redirect(RedirectBehavior::Custom(Arc::new(|attempt: &RedirectAttempt| {
+      match attempt.next.path() {
+        "/done" => RedirectAction::Stop,
+        _ => RedirectAction::Follow,
+      }
+    })))
+    .build();
+
+  let response = stop_at_done.get(server.redirect_url(302, &server.redirect_url(307, "/done")), None).await.unwrap();
+
+  assert_eq!(response.status(), 307);
+  assert_eq!(response.redirects().len(), 1);
+  assert_eq!(server.requests().len(), 2);
+
+  let reject = Impit::builder()
+    .with_ignore_tls_errors(true)
+    .with_redirect(RedirectBehavior::Custom(Arc::new(|attempt: &RedirectAttempt| {
+      RedirectAction::Error(format!("unexpected {} redirect", attempt.hop().status).into())
+    })))
+    .build();
+
+  match reject.get(server.redirect_url(308, "/done"), None).await {
+    Err(ErrorType::RedirectRejected(err)) => assert_eq!(err.to_string(), "unexpected 308 Permanent Redirect redirect"),
+    other => panic!("expected RedirectRejected, got {:?}", other.map(|response| response.status())),
+  }
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use common::TestServer;
+use impit::emulation::{Browser, ChromeVersion, FirefoxVersion};
+use impit::impit::Impit;
+use impit::request::{Method, RequestContext};
+
+#[tokio::test]
+async fn same_origin_requests_send_the_full_referer() {
+  let server = TestServer::start().await;
+
+  let impit = Impit::builder()
+    .with_browser(Browser::Chrome(ChromeVersion::V131))
+    .with_ignore_tls_errors(true)
+    .build();
+
+  let referrer = format!("{}products?page=2#reviews", server.url());
+
+  impit.request(Method::GET, server.url())
+    .referrer(&referrer)
+    .send()
+    .await
+    .unwrap();
+
+  let request = server.requests().pop().unwrap();
+
+  assert_eq!(request.header("sec-fetch-site"), Some("same-origin"));
+  assert_eq!(request.header("referer"), Some(referrer.trim_end_matches("#reviews")));
+  assert_eq!(request.header("origin"), None);
+
+  let names: Vec<&str> = request.headers.iter().map(|(name, _)| name.as_str()).collect();
+  let referer = names.iter().position(|name| *name == "referer").unwrap();
+  assert_eq!(names[referer + 1], "accept-encoding");
+}
+
+#[tokio::test]
+async fn cross_site_requests_only_send_the_origin() {
+  let server = TestServer::start().await;
+
+  let impit = Impit::builder()
+    .with_browser(Browser::Chrome(ChromeVersion::V131))
+    .with_ignore_tls_errors(true)
+    .build();
+
+  impit.request(Method::POST, server.url())
+    .referrer("https://shop.example.com/cart?item=1")
+    .context(RequestContext::Fetch)
+    .body("{}")
+    .send()
+    .await
+    .unwrap();
+
+  let request = server.requests().pop().unwrap();
+
+  assert_eq!(request.header("sec-fetch-site"), Some("cross-site"));
+  assert_eq!(request.header("referer"), Some("https://shop.example.com/"));
+  assert_eq!(request.header("origin"), Some("https://shop.example.com"));
+
+  let names: Vec<&str> = request.headers.iter().map(|(name, _)| name.as_str()).collect();
+  let origin = names.iter().position(|name| *name == "origin").unwrap();
+  assert_eq!(names[origin - 1], "sec-ch-ua-platform");
+}
+
+#[tokio::test]
+async fn requests_without_referrer_are_typed_navigations() {
+  let server = TestServer::start().await;
+
+  let impit = Impit::builder()
+    .with_browser(Browser::Firefox(FirefoxVersion::V133))
+    .with_ignore_tls_errors(true)
+    .build();
+
+  impit.request(Method::POST, server.url()).body("a=1").send().await.unwrap();
+
+  let request = server.requests().pop().unwrap();
+
+  assert_eq!(request.header("sec-fetch-site"), Some("none"));
+  assert_eq!(request.header("referer"), None);
+  assert_eq!(request.header("origin"), None);
+}
+
+#[tokio::test]
+async fn custom_referer_overrides_the_computed_one() {
+  let server = TestServer::start().await;
+
+  let impit = Impit::builder()
+    .with_browser(Browser::Firefox(FirefoxVersion::V133))
+    .with_ignore_tls_errors(true)
+    .build();
+
+  impit.request(Method::GET, server.url())
+    .referrer("https://example.com/")
+    .header("referer", "https://example.com/search")
+    .send()
+    .await
+    .unwrap();
+
+  let request = server.requests().pop().unwrap();
+
+  assert_eq!(request.header("referer"), Some("https://example.com/search"));
+  assert_eq!(request.headers.iter().filter(|(name, _)| name.eq_ignore_ascii_case("referer")).count(), 1);
+}
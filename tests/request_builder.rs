@@ -0,0 +1,107 @@
+mod common;
+
+use std::time::Duration;
+
+use common::TestServer;
+use impit::emulation::{Browser, FirefoxVersion};
+use impit::impit::{ErrorType, Impit};
+use impit::request::{CustomHeader, HeaderPosition, Method, RequestOptions};
+
+#[tokio::test]
+async fn request_builder_sends_the_configured_request() {
+  let server = TestServer::start().await;
+
+  let impit = Impit::builder()
+    .with_browser(Browser::Firefox(FirefoxVersion::V133))
+    .with_ignore_tls_errors(true)
+    .build();
+
+  let response = impit.request(Method::POST, server.url().as_str())
+    .header("x-api-key", "secret")
+    .query(&[("page", "2"), ("q", "a b")])
+    .json(&serde_json::json!({ "name": "impit" }))
+    .timeout(Duration::from_secs(5))
+    .send()
+    .await
+    .unwrap();
+
+  assert_eq!(response.status(), 200);
+
+  let request = server.requests().pop().unwrap();
+  assert_eq!(request.method, "POST");
+  assert_eq!(request.path, "/?page=2&q=a+b");
+  assert_eq!(request.header("x-api-key"), Some("secret"));
+  assert_eq!(request.header("content-type"), Some("application/json"));
+}
+
+#[tokio::test]
+async fn request_builder_supports_extension_methods() {
+  let server = TestServer::start().await;
+
+  let impit = Impit::builder()
+    .with_ignore_tls_errors(true)
+    .build();
+
+  let propfind = Method::from_bytes(b"PROPFIND").unwrap();
+  impit.request(propfind, server.url()).send().await.unwrap();
+
+  assert_eq!(server.requests().pop().unwrap().method, "PROPFIND");
+}
+
+#[tokio::test]
+async fn invalid_headers_fail_the_request() {
+  let server = TestServer::start().await;
+
+  let impit = Impit::builder()
+    .with_browser(Browser::Firefox(FirefoxVersion::V133))
+    .with_ignore_tls_errors(true)
+    .build();
+
+  let error = impit.request(Method::GET, server.url().as_str())
+    .header("x-valid", "value")
+    .header("x invalid", "value")
+    .header_at("x-injected", "value\r\nx-other: 1", HeaderPosition::End)
+    .send()
+    .await
+    .unwrap_err();
+
+  // The first invalid header is reported.
+  assert!(
+    matches!(&error, ErrorType::InvalidHeader { name, value } if name == "x invalid" && value == "value"),
+    "unexpected error: {:?}",
+    error,
+  );
+
+  let error = impit.request(Method::GET, server.url().as_str())
+    .header_at("x-injected", "value\r\nx-other: 1", HeaderPosition::End)
+    .send()
+    .await
+    .unwrap_err();
+
+  assert!(matches!(error, ErrorType::InvalidHeader { .. }), "unexpected error: {:?}", error);
+  assert!(server.requests().is_empty());
+}
+
+#[tokio::test]
+async fn invalid_headers_in_request_options_fail_the_request() {
+  let server = TestServer::start().await;
+
+  let impit = Impit::builder()
+    .with_browser(Browser::Firefox(FirefoxVersion::V133))
+    .with_ignore_tls_errors(true)
+    .build();
+
+  let options = RequestOptions {
+    headers: vec![CustomHeader::new("x bad", "v")],
+    ..Default::default()
+  };
+
+  let error = impit.get(server.url(), Some(options)).await.unwrap_err();
+
+  assert!(
+    matches!(&error, ErrorType::InvalidHeader { name, value } if name == "x bad" && value == "v"),
+    "unexpected error: {:?}",
+    error,
+  );
+  assert!(server.requests().is_empty());
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use common::TestServer;
+use impit::emulation::{Browser, ChromeVersion, FirefoxVersion};
+use impit::impit::Impit;
+use impit::request::{Method, RequestContext};
+
+#[tokio::test]
+async fn fetch_requests_send_the_fetch_headers() {
+  let server = TestServer::start().await;
+
+  let impit = Impit::builder()
+    .with_browser(Browser::Chrome(ChromeVersion::V131))
+    .with_ignore_tls_errors(true)
+    .build();
+
+  impit.request(Method::POST, server.url())
+    .context(RequestContext::Fetch)
+    .json(&serde_json::json!({ "query": "impit" }))
+    .send()
+    .await
+    .unwrap();
+
+  let request = server.requests().pop().unwrap();
+
+  assert_eq!(request.header("accept"), Some("*/*"));
+  // No referrer was given, the request is made as if from a page of the requested origin.
+  assert_eq!(request.header("sec-fetch-site"), Some("same-origin"));
+  assert_eq!(request.header("origin"), Some(server.url().trim_end_matches('/')));
+  assert_eq!(request.header("referer"), None);
+  assert_eq!(request.header("sec-fetch-mode"), Some("cors"));
+  assert_eq!(request.header("sec-fetch-dest"), Some("empty"));
+  assert_eq!(request.header("priority"), Some("u=1, i"));
+  assert_eq!(request.header("sec-fetch-user"), None);
+  assert_eq!(request.header("upgrade-insecure-requests"), None);
+}
+
+#[tokio::test]
+async fn navigation_is_the_default_context() {
+  let server = TestServer::start().await;
+
+  let impit = Impit::builder()
+    .with_browser(Browser::Firefox(FirefoxVersion::V133))
+    .with_ignore_tls_errors(true)
+    .build();
+
+  impit.get(server.url(), None).await.unwrap();
+  impit.request(Method::GET, server.url()).context(RequestContext::Image).send().await.unwrap();
+
+  let requests = server.requests();
+
+  assert_eq!(requests[0].header("sec-fetch-dest"), Some("document"));
+  assert_eq!(requests[0].header("sec-fetch-user"), Some("?1"));
+  assert_eq!(requests[0].header("sec-fetch-site"), Some("none"));
+
+  assert_eq!(requests[1].header("sec-fetch-dest"), Some("image"));
+  assert_eq!(requests[1].header("sec-fetch-mode"), Some("no-cors"));
+  assert!(requests[1].header("accept").unwrap().starts_with("image/avif"));
+  assert_eq!(requests[1].header("sec-fetch-site"), Some("cross-site"));
+}
+
+#[tokio::test]
+async fn iframes_without_referrer_are_cross_site() {
+  let server = TestServer::start().await;
+
+  let impit = Impit::builder()
+    .with_browser(Browser::Chrome(ChromeVersion::V131))
+    .with_ignore_tls_errors(true)
+    .build();
+
+  impit.request(Method::GET, server.url()).context(RequestContext::Iframe).send().await.unwrap();
+  impit.request(Method::GET, server.url())
+    .context(RequestContext::Iframe)
+    .referrer(server.url())
+    .send()
+    .await
+    .unwrap();
+
+  let requests = server.requests();
+
+  assert_eq!(requests[0].header("sec-fetch-dest"), Some("iframe"));
+  assert_eq!(requests[0].header("sec-fetch-site"), Some("cross-site"));
+  assert_eq!(requests[1].header("sec-fetch-site"), Some("same-origin"));
+}
+
+#[tokio::test]
+async fn cors_requests_without_referrer_are_consistent() {
+  let server = TestServer::start().await;
+
+  let impit = Impit::builder()
+    .with_browser(Browser::Firefox(FirefoxVersion::V133))
+    .with_ignore_tls_errors(true)
+    .build();
+
+  impit.request(Method::GET, server.url()).context(RequestContext::Fetch).send().await.unwrap();
+  impit.request(Method::GET, server.url()).context(RequestContext::Font).send().await.unwrap();
+
+  // A cross-site CORS request would have to send the `Origin` of the unknown page.
+  for request in server.requests() {
+    assert_eq!(request.header("sec-fetch-mode"), Some("cors"));
+    assert_eq!(request.header("sec-fetch-site"), Some("same-origin"));
+    assert_eq!(request.header("origin"), None);
+    assert_eq!(request.header("referer"), None);
+  }
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::dns::TestDnsServer;
+use common::TestServer;
+use hickory_proto::rr::RecordType;
+use impit::dns::{DnsAnswer, DnsConfig, DnsFuture, HttpsRecord, NameServer, Resolver};
+use impit::impit::Impit;
+
+/// Resolves every host to `127.0.0.1`, counting the lookups.
+struct LocalResolver {
+  ttl: Duration,
+  ip_lookups: AtomicUsize,
+  https_lookups: AtomicUsize,
+}
+
+impl LocalResolver {
+  fn new(ttl: Duration) -> Arc<LocalResolver> {
+    Arc::new(LocalResolver { ttl, ip_lookups: AtomicUsize::new(0), https_lookups: AtomicUsize::new(0) })
+  }
+}
+
+impl Resolver for LocalResolver {
+  fn lookup_ip<'a>(&'a self, _host: &'a str) -> DnsFuture<'a, IpAddr> {
+    Box::pin(async move {
+      self.ip_lookups.fetch_add(1, Ordering::SeqCst);
+      Ok(DnsAnswer { records: vec![IpAddr::V4(Ipv4Addr::LOCALHOST)], ttl: self.ttl })
+    })
+  }
+
+  fn lookup_https<'a>(&'a self, _host: &'a str) -> DnsFuture<'a, HttpsRecord> {
+    Box::pin(async move {
+      self.https_lookups.fetch_add(1, Ordering::SeqCst);
+      Ok(DnsAnswer { records: vec![], ttl: self.ttl })
+    })
+  }
+}
+
+fn port(server: &TestServer) -> u16 {
+  url::Url::parse(&server.url()).unwrap().port().unwrap()
+}
+
+#[tokio::test]
+async fn custom_resolver_is_used_for_connections_and_https_records() {
+  let server = TestServer::start().await;
+  let resolver = LocalResolver::new(Duration::from_secs(60));
+
+  let impit = Impit::builder()
+    .with_http3()
+    .with_resolver(resolver.clone())
+    .with_ignore_tls_errors(true)
+    .build();
+
+  let response = impit.get(format!("https://example.test:{}/", port(&server)), None).await.unwrap();
+
+  assert_eq!(response.status(), 200);
+  assert_eq!(resolver.ip_lookups.load(Ordering::SeqCst), 1);
+  assert_eq!(resolver.https_lookups.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn answers_are_cached_for_their_ttl() {
+  // Two servers on the same host, so the second request opens a new connection.
+  let first = TestServer::start().await;
+  let second = TestServer::start().await;
+
+  for (ttl, expected_lookups) in [(60, 1), (0, 2)] {
+    let resolver = LocalResolver::new(Duration::from_secs(ttl));
+
+    let impit = Impit::builder()
+      .with_resolver(resolver.clone())
+      .with_ignore_tls_errors(true)
+      .build();
+
+    for server in [&first, &second] {
+      let response = impit.get(format!("https://example.test:{}/", port(server)), None).await.unwrap();
+      assert_eq!(response.status(), 200);
+    }
+
+    assert_eq!(resolver.ip_lookups.load(Ordering::SeqCst), expected_lookups, "ttl {}", ttl);
+  }
+}
+
+#[tokio::test]
+async fn huge_ttls_are_capped() {
+  let server = TestServer::start().await;
+  let resolver = LocalResolver::new(Duration::MAX);
+
+  let impit = Impit::builder()
+    .with_http3()
+    .with_resolver(resolver.clone())
+    .with_ignore_tls_errors(true)
+    .build();
+
+  for _ in 0..2 {
+    let response = impit.get(format!("https://example.test:{}/", port(&server)), None).await.unwrap();
+    assert_eq!(response.status(), 200);
+  }
+
+  // Both answers are still cached.
+  assert_eq!(resolver.ip_lookups.load(Ordering::SeqCst), 1);
+  assert_eq!(resolver.https_lookups.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn host_overrides_skip_the_lookup() {
+  let server = TestServer::start().await;
+  let dns = TestDnsServer::start(&[]).await;
+
+  let impit = Impit::builder()
+    .with_dns(DnsConfig::NameServers(vec![NameServer::udp(dns.address())]))
+    .with_host_override("Override.test", vec![IpAddr::V4(Ipv4Addr::LOCALHOST)])
+    .with_ignore_tls_errors(true)
+    .build();
+
+  let response = impit.get(format!("https://override.test:{}/", port(&server)), None).await.unwrap();
+  assert_eq!(response.status(), 200);
+
+  // The TLS handshake still uses the requested host.
+  let requests = server.requests();
+  assert_eq!(requests[0].connection.client_hello.server_name.as_deref(), Some("override.test"));
+
+  // The other hosts go through the configured nameservers.
+  let missing = impit.get(format!("https://missing.test:{}/", port(&server)), None).await;
+  assert!(missing.is_err());
+
+  let queries = dns.queries();
+  assert!(queries.iter().all(|(host, _)| host != "override.test"), "{:?}", queries);
+  assert!(queries.contains(&(String::from("missing.test"), RecordType::A)), "{:?}", queries);
+}
+
+#[tokio::test]
+async fn proxy_resolves_the_hosts() {
+  let resolver = LocalResolver::new(Duration::from_secs(60));
+
+  let impit = Impit::builder()
+    .with_http3()
+    .with_resolver(resolver.clone())
+    // Nothing listens on the discard port, the request fails on connecting to the proxy.
+    .with_proxy(String::from("http://127.0.0.1:9"))
+    .with_default_timeout(Duration::from_secs(5))
+    .build();
+
+  let response = impit.get("https://example.test/", None).await;
+
+  assert!(response.is_err());
+  assert_eq!(resolver.ip_lookups.load(Ordering::SeqCst), 0);
+  assert_eq!(resolver.https_lookups.load(Ordering::SeqCst), 0);
+}
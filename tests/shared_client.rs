@@ -0,0 +1,39 @@
+mod common;
+
+use common::TestServer;
+use impit::emulation::{Browser, ChromeVersion};
+use impit::impit::Impit;
+
+const TASKS: usize = 50;
+
+fn assert_shareable<T: Clone + Send + Sync + 'static>() {}
+
+#[test]
+fn impit_is_shareable() {
+  assert_shareable::<Impit>();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn clones_can_be_used_from_concurrent_tasks() {
+  let server = TestServer::start().await;
+
+  let impit = Impit::builder()
+    .with_browser(Browser::Chrome(ChromeVersion::V131))
+    .with_ignore_tls_errors(true)
+    .build();
+
+  let tasks: Vec<_> = (0..TASKS)
+    .map(|_| {
+      let impit = impit.clone();
+      let url = server.url();
+
+      tokio::spawn(async move { impit.get(url, None).await })
+    })
+    .collect();
+
+  for task in tasks {
+    assert_eq!(task.await.unwrap().unwrap().status(), 200);
+  }
+
+  assert_eq!(server.requests().len(), TASKS);
+}
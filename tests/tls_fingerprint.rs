@@ -0,0 +1,153 @@
+use impit::emulation::{Browser, ChromeVersion, ClientHello, FirefoxVersion, SafariVersion, TlsFingerprint};
+use impit::impit::Impit;
+
+/// The Chrome ClientHello from the JA4 specification (https://github.com/FoxIO-LLC/ja4/blob/main/technical_details/JA4.md),
+/// with GREASE values added and the extensions in a shuffled order.
+fn ja4_example() -> ClientHello {
+  ClientHello {
+    legacy_version: 0x0303,
+    cipher_suites: vec![
+      0x0a0a, 0x1301, 0x1302, 0x1303, 0xc02b, 0xc02f, 0xc02c, 0xc030,
+      0xcca9, 0xcca8, 0xc013, 0xc014, 0x009c, 0x009d, 0x002f, 0x0035,
+    ],
+    extensions: vec![
+      0x1a1a, 0x0033, 0x0010, 0x4469, 0x0017, 0x002d, 0x000d, 0x0005, 0x0023,
+      0x0012, 0x002b, 0xff01, 0x000b, 0x0000, 0x001b, 0x000a, 0x2a2a, 0x0015,
+    ],
+    server_name: Some(String::from("example.com")),
+    supported_groups: vec![0x4a4a, 0x001d, 0x0017, 0x0018],
+    ec_point_formats: vec![0],
+    signature_algorithms: vec![0x0403, 0x0804, 0x0401, 0x0503, 0x0805, 0x0501, 0x0806, 0x0601],
+    alpn: vec![String::from("h2"), String::from("http/1.1")],
+    supported_versions: vec![0x7a7a, 0x0304, 0x0303],
+    key_shares: vec![0x4a4a, 0x001d],
+  }
+}
+
+#[test]
+fn ja4_matches_the_specification_example() {
+  let fingerprint = TlsFingerprint::from_client_hello(&ja4_example());
+
+  assert_eq!(fingerprint.ja4, "t13d1516h2_8daaf6152771_e5627efa2ab1");
+}
+
+#[test]
+fn ja3_leaves_out_grease_values() {
+  let fingerprint = TlsFingerprint::from_client_hello(&ja4_example());
+
+  assert_eq!(
+    fingerprint.ja3,
+    "771,4865-4866-4867-49195-49199-49196-49200-52393-52392-49171-49172-156-157-47-53,\
+     51-16-17513-23-45-13-5-35-18-43-65281-11-0-27-10-21,29-23-24,0",
+  );
+  assert_eq!(fingerprint.ja3_hash, "23fbe9c1b6621994091c88950dfac45d");
+}
+
+#[test]
+fn ja3n_sorts_the_extensions() {
+  let fingerprint = TlsFingerprint::from_client_hello(&ja4_example());
+
+  assert_eq!(
+    fingerprint.ja3n,
+    "771,4865-4866-4867-49195-49199-49196-49200-52393-52392-49171-49172-156-157-47-53,\
+     0-5-10-11-13-16-18-21-23-27-35-43-45-51-17513-65281,29-23-24,0",
+  );
+  assert_eq!(fingerprint.ja3n_hash, "aa56c057ad164ec4fdcb7a5a283be9fc");
+
+  // Chrome shuffles the extensions for every connection - only JA3 changes.
+  let mut shuffled = ja4_example();
+  shuffled.extensions.reverse();
+  let shuffled = TlsFingerprint::from_client_hello(&shuffled);
+
+  assert_ne!(shuffled.ja3, fingerprint.ja3);
+  assert_eq!(shuffled.ja3n, fingerprint.ja3n);
+  assert_eq!(shuffled.ja4, fingerprint.ja4);
+}
+
+#[test]
+fn ja4_describes_sni_and_alpn() {
+  // Without the SNI and ALPN extensions. Both are left out of the extension hash anyway.
+  let mut client_hello = ja4_example();
+  client_hello.extensions.retain(|extension| *extension != 0x0000 && *extension != 0x0010);
+  client_hello.server_name = None;
+  client_hello.alpn = vec![];
+
+  assert_eq!(TlsFingerprint::from_client_hello(&client_hello).ja4, "t13i151400_8daaf6152771_e5627efa2ab1");
+
+  // ALPN values starting or ending with a non-alphanumeric character are hex-encoded.
+  let mut client_hello = ja4_example();
+  client_hello.alpn = vec![String::from("*x")];
+
+  assert!(TlsFingerprint::from_client_hello(&client_hello).ja4.starts_with("t13d151628_"));
+}
+
+#[test]
+fn captured_client_hello_fingerprints() {
+  let client_hello = ClientHello::parse(include_bytes!("fixtures/curl_client_hello.bin")).unwrap();
+  let fingerprint = TlsFingerprint::from_client_hello(&client_hello);
+
+  assert_eq!(fingerprint.ja3_hash, "0149f47eabf9a20d0893e2a44e5a6323");
+  assert_eq!(fingerprint.ja4, "t13d3112h2_e8f1e7e78f70_b26ce05bbdd6");
+}
+
+// The fingerprints of the real browser releases. Chrome and Firefox share them between the pinned releases,
+// only the post-quantum group of Chrome changed (`X25519Kyber768Draft00` in 125, `X25519MLKEM768` in 131).
+const CHROME_JA4: &str = "t13d1516h2_8daaf6152771_02713d6af862";
+const FIREFOX_JA4: &str = "t13d1716h2_5b57614c22b0_eeeea6562960";
+const SAFARI_JA4: &str = "t13d2014h2_a09f3c656075_14788d8d241b";
+
+const CHROME_125_JA3N: &str = "771,4865-4866-4867-49195-49199-49196-49200-52393-52392-49171-49172-156-157-47-53,\
+  0-5-10-11-13-16-18-23-27-35-43-45-51-17513-65037-65281,25497-29-23-24,0";
+const CHROME_131_JA3N: &str = "771,4865-4866-4867-49195-49199-49196-49200-52393-52392-49171-49172-156-157-47-53,\
+  0-5-10-11-13-16-18-23-27-35-43-45-51-17513-65037-65281,4588-29-23-24,0";
+const FIREFOX_JA3N: &str = "771,4865-4867-4866-49195-49199-52393-52392-49196-49200-49162-49161-49171-49172-156-157-47-53,\
+  0-5-10-11-13-16-23-27-28-34-35-43-45-51-65037-65281,29-23-24-256-257,0";
+const SAFARI_JA3N: &str = "771,4865-4866-4867-49196-49195-52393-49200-49199-52392-49162-49161-49172-49171-157-156-53-47-49160-49170-10,\
+  0-5-10-11-13-16-18-21-23-27-43-45-51-65281,29-23-24-25,0";
+
+#[test]
+fn presets_have_the_browser_tls_fingerprints() {
+  let expected = [
+    (Browser::Chrome(ChromeVersion::V125), CHROME_JA4, CHROME_125_JA3N),
+    (Browser::Chrome(ChromeVersion::V131), CHROME_JA4, CHROME_131_JA3N),
+    (Browser::Edge(ChromeVersion::V125), CHROME_JA4, CHROME_125_JA3N),
+    (Browser::Edge(ChromeVersion::V131), CHROME_JA4, CHROME_131_JA3N),
+    (Browser::ChromeAndroid(ChromeVersion::V125), CHROME_JA4, CHROME_125_JA3N),
+    (Browser::ChromeAndroid(ChromeVersion::V131), CHROME_JA4, CHROME_131_JA3N),
+    (Browser::Firefox(FirefoxVersion::V128), FIREFOX_JA4, FIREFOX_JA3N),
+    (Browser::Firefox(FirefoxVersion::V133), FIREFOX_JA4, FIREFOX_JA3N),
+    (Browser::Safari(SafariVersion::V17), SAFARI_JA4, SAFARI_JA3N),
+    (Browser::Safari(SafariVersion::V18), SAFARI_JA4, SAFARI_JA3N),
+    (Browser::SafariIos(SafariVersion::V17), SAFARI_JA4, SAFARI_JA3N),
+    (Browser::SafariIos(SafariVersion::V18), SAFARI_JA4, SAFARI_JA3N),
+  ];
+
+  for (browser, ja4, ja3n) in expected {
+    let impit = Impit::builder()
+      .with_browser(browser)
+      .build();
+
+    let fingerprint = impit.tls_fingerprint().unwrap();
+
+    assert_eq!(fingerprint.ja4, ja4, "{:?}", browser);
+    assert_eq!(fingerprint.ja3n, ja3n, "{:?}", browser);
+  }
+}
+
+#[test]
+fn tls_fingerprint_follows_the_http_version() {
+  let http1 = Impit::builder()
+    .with_browser(Browser::Chrome(ChromeVersion::V131))
+    .with_http1_only()
+    .build();
+
+  assert_eq!(http1.tls_fingerprint().unwrap().ja4, "t13d1516h1_8daaf6152771_02713d6af862");
+
+  // The HTTP/3 requests use QUIC, the TCP connections still offer `h2`.
+  let http3 = Impit::builder()
+    .with_browser(Browser::Chrome(ChromeVersion::V131))
+    .with_http3()
+    .build();
+
+  assert_eq!(http3.tls_fingerprint().unwrap().ja4, CHROME_JA4);
+}